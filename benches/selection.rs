@@ -0,0 +1,82 @@
+//! Compares proxy selection on the lock-free snapshot with the previous design,
+//! which filtered the healthy proxies under a read lock and cloned the choice.
+//!
+//! Setup health checks every proxy once, which takes a while for the larger pool.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use parking_lot::{Mutex, RwLock};
+use reqwest_proxy_pool::{Proxy, ProxyPool, ProxyPoolConfig, ProxySelectionStrategy, ProxyStatus};
+use std::hint::black_box;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+
+/// The previous design: a locked `Vec`, filtered on every selection.
+struct LockedVecPool {
+    proxies: RwLock<Vec<Proxy>>,
+    last_proxy_index: Mutex<usize>,
+}
+
+impl LockedVecPool {
+    fn get_proxy(&self) -> Option<Proxy> {
+        let proxies = self.proxies.read();
+        let healthy_proxies: Vec<&Proxy> = proxies.iter()
+            .filter(|p| p.status() == ProxyStatus::Healthy && p.weight > 0.0)
+            .collect();
+        if healthy_proxies.is_empty() {
+            return None;
+        }
+
+        let mut last_index = self.last_proxy_index.lock();
+        *last_index = (*last_index + 1) % healthy_proxies.len();
+        Some(healthy_proxies[*last_index].clone())
+    }
+}
+
+/// Build a pool of `size` local proxies and mark them all healthy.
+fn build_pool(runtime: &Runtime, size: usize) -> Arc<ProxyPool> {
+    let proxies: Vec<String> = (0..size)
+        .map(|i| format!("socks5://127.0.0.1:{}", 20000 + i))
+        .collect();
+    let config = ProxyPoolConfig::builder()
+        .proxies(proxies.clone())
+        .unwrap()
+        .allow_unsafe_addresses(true)
+        .health_check_url("http://127.0.0.1:9/")
+        .health_check_timeout(Duration::from_millis(200))
+        .health_check_interval(Duration::from_secs(3600))
+        .selection_strategy(ProxySelectionStrategy::RoundRobin)
+        .build();
+
+    let pool = runtime.block_on(ProxyPool::new(config)).expect("pool");
+    // Nothing listens on these ports, report successes to make them selectable
+    for proxy in &proxies {
+        pool.report_proxy_success(&proxy.parse().expect("proxy url"));
+    }
+    pool
+}
+
+fn bench_get_proxy(c: &mut Criterion) {
+    let runtime = Runtime::new().expect("runtime");
+    let mut group = c.benchmark_group("get_proxy");
+
+    for size in [100, 1000] {
+        let pool = build_pool(&runtime, size);
+        let locked = LockedVecPool {
+            proxies: RwLock::new((0..size).map(|_| Proxy::clone(&pool.get_proxy().expect("proxy"))).collect()),
+            last_proxy_index: Mutex::new(0),
+        };
+
+        group.bench_with_input(BenchmarkId::new("locked_vec", size), &locked, |b, locked| {
+            b.iter(|| black_box(locked.get_proxy()))
+        });
+        group.bench_with_input(BenchmarkId::new("snapshot", size), &pool, |b, pool| {
+            b.iter(|| black_box(pool.get_proxy().ok()))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_get_proxy);
+criterion_main!(benches);
@@ -1,8 +1,97 @@
 //! Error types for the reqwest-proxy-pool crate.
 
+use std::path::PathBuf;
+use std::sync::Arc;
 use thiserror::Error;
 
 /// Error returned when no healthy proxy is available in the pool.
 #[derive(Debug, Error)]
 #[error("No proxy available in pool")]
 pub struct NoProxyAvailable;
+
+/// Error returned when a string is not a valid proxy URL.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Invalid proxy URL {url:?}: {reason}")]
+pub struct InvalidProxyUrl {
+    /// The rejected input.
+    pub url: String,
+    /// Why the input was rejected.
+    pub reason: String,
+}
+
+impl InvalidProxyUrl {
+    pub(crate) fn new(url: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            reason: reason.into(),
+        }
+    }
+}
+
+/// Error returned when a proxy source cannot provide proxies.
+#[derive(Debug, Error)]
+pub enum SourceError {
+    /// The source file could not be read.
+    #[error("failed to read {path}: {error}")]
+    Io {
+        /// Path of the file.
+        path: PathBuf,
+        /// Underlying IO error.
+        #[source]
+        error: std::io::Error,
+    },
+    /// The environment variable holding the proxy list is not set.
+    #[error("environment variable {0} is not set")]
+    MissingEnvVar(String),
+    /// The HTTP request for the source failed.
+    #[error("request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    /// The source responded with an unexpected HTTP status.
+    #[error("unexpected HTTP status {0}")]
+    HttpStatus(reqwest::StatusCode),
+    /// The fetch did not complete within the timeout of its retry policy.
+    #[error("timed out after {0:?}")]
+    Timeout(std::time::Duration),
+    /// The source content could not be parsed.
+    #[error("failed to parse proxy list: {0}")]
+    Parse(String),
+    /// The source yielded no proxies.
+    #[error("source yielded no proxies")]
+    Empty,
+    /// Any other error raised by a custom source.
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl SourceError {
+    /// Whether the error may go away when the fetch is retried.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            SourceError::Http(_) | SourceError::Timeout(_) | SourceError::Other(_) => true,
+            SourceError::HttpStatus(status) => status.is_server_error() || *status == reqwest::StatusCode::TOO_MANY_REQUESTS,
+            SourceError::Io { .. } | SourceError::MissingEnvVar(_) | SourceError::Parse(_) | SourceError::Empty => false,
+        }
+    }
+}
+
+/// Error returned when the proxy pool cannot be created.
+#[derive(Debug, Error)]
+pub enum ProxyPoolError {
+    /// A source failed while the pool was created in strict mode.
+    #[error("source {name} failed: {error}")]
+    Source {
+        /// Name of the failed source.
+        name: String,
+        /// Why the source failed.
+        #[source]
+        error: Arc<SourceError>,
+    },
+    /// Fewer than `min_available_proxies` proxies were healthy when the pool was created.
+    #[error("only {available} of the required {required} proxies are available")]
+    NotEnoughProxies {
+        /// Number of available proxies.
+        available: usize,
+        /// Configured minimum.
+        required: usize,
+    },
+}
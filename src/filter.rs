@@ -0,0 +1,356 @@
+//! Allow/deny filters applied to proxy entries before they join the pool.
+
+use crate::proxy::{ProxyEntry, ProxyScheme};
+use ipnet::{AddrParseError, IpNet};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use std::fmt;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+/// Reason a proxy entry was rejected by a [`ProxyFilter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FilterRejection {
+    /// The address is in a denied network.
+    DeniedNetwork,
+    /// The address is not in any allowed network.
+    NotAllowedNetwork,
+    /// The port is outside the allowed ranges.
+    Port,
+    /// The scheme is not allowed.
+    Scheme,
+    /// The custom predicate rejected the entry.
+    Predicate,
+    /// The address is loopback, private, link-local or otherwise internal.
+    UnsafeAddress,
+}
+
+impl fmt::Display for FilterRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FilterRejection::DeniedNetwork => "denied network",
+            FilterRejection::NotAllowedNetwork => "network not allowed",
+            FilterRejection::Port => "port not allowed",
+            FilterRejection::Scheme => "scheme not allowed",
+            FilterRejection::Predicate => "rejected by predicate",
+            FilterRejection::UnsafeAddress => "unsafe address",
+        })
+    }
+}
+
+type Predicate = Arc<dyn Fn(&ProxyEntry) -> bool + Send + Sync>;
+
+/// Filters deciding which parsed proxy entries may join the pool.
+///
+/// Every configured rule must pass. Empty allow lists allow everything.
+/// Network rules match IP addresses only; when networks are allowed, hostname
+/// entries are rejected unless `resolve_hostnames` turns them into addresses first.
+#[derive(Clone, Default)]
+pub struct ProxyFilter {
+    allowed_networks: Vec<IpNet>,
+    denied_networks: Vec<IpNet>,
+    ports: Vec<RangeInclusive<u16>>,
+    schemes: Vec<ProxyScheme>,
+    predicate: Option<Predicate>,
+}
+
+impl ProxyFilter {
+    /// Create a filter that accepts every entry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow addresses in the given network.
+    pub fn allow_network(mut self, network: IpNet) -> Self {
+        self.allowed_networks.push(network);
+        self
+    }
+
+    /// Deny addresses in the given network.
+    pub fn deny_network(mut self, network: IpNet) -> Self {
+        self.denied_networks.push(network);
+        self
+    }
+
+    /// Allow addresses in the given CIDR networks, e.g. `"203.0.113.0/24"`.
+    ///
+    /// Fails if any network is invalid, so a typo cannot leave the allow list empty.
+    pub fn allow_cidrs(mut self, networks: Vec<impl AsRef<str>>) -> Result<Self, AddrParseError> {
+        self.allowed_networks.extend(parse_networks(networks)?);
+        Ok(self)
+    }
+
+    /// Deny addresses in the given CIDR networks, e.g. `"10.0.0.0/8"`.
+    ///
+    /// Fails if any network is invalid.
+    pub fn deny_cidrs(mut self, networks: Vec<impl AsRef<str>>) -> Result<Self, AddrParseError> {
+        self.denied_networks.extend(parse_networks(networks)?);
+        Ok(self)
+    }
+
+    /// Allow ports in the given range, e.g. `1080..=1090`.
+    pub fn allow_ports(mut self, ports: RangeInclusive<u16>) -> Self {
+        self.ports.push(ports);
+        self
+    }
+
+    /// Allow only the given schemes.
+    pub fn allow_schemes(mut self, schemes: impl IntoIterator<Item = ProxyScheme>) -> Self {
+        self.schemes.extend(schemes);
+        self
+    }
+
+    /// Reject entries for which the predicate returns `false`.
+    pub fn predicate<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&ProxyEntry) -> bool + Send + Sync + 'static,
+    {
+        self.predicate = Some(Arc::new(predicate));
+        self
+    }
+
+    /// Check an entry, returning why it is rejected if it is.
+    pub fn check(&self, entry: &ProxyEntry) -> Result<(), FilterRejection> {
+        let url = &entry.url;
+
+        if !self.schemes.is_empty() && !self.schemes.contains(&url.scheme()) {
+            return Err(FilterRejection::Scheme);
+        }
+        if !self.ports.is_empty() && !self.ports.iter().any(|range| range.contains(&url.port())) {
+            return Err(FilterRejection::Port);
+        }
+        match url.ip() {
+            Some(ip) => {
+                if self.denied_networks.iter().any(|net| net.contains(&ip)) {
+                    return Err(FilterRejection::DeniedNetwork);
+                }
+                if !self.allowed_networks.is_empty() && !self.allowed_networks.iter().any(|net| net.contains(&ip)) {
+                    return Err(FilterRejection::NotAllowedNetwork);
+                }
+            }
+            None if !self.allowed_networks.is_empty() => return Err(FilterRejection::NotAllowedNetwork),
+            None => {}
+        }
+        if let Some(predicate) = &self.predicate {
+            if !predicate(entry) {
+                return Err(FilterRejection::Predicate);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for ProxyFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProxyFilter")
+            .field("allowed_networks", &self.allowed_networks)
+            .field("denied_networks", &self.denied_networks)
+            .field("ports", &self.ports)
+            .field("schemes", &self.schemes)
+            .field("predicate", &self.predicate.as_ref().map(|_| "<fn>"))
+            .finish()
+    }
+}
+
+fn parse_networks(networks: Vec<impl AsRef<str>>) -> Result<Vec<IpNet>, AddrParseError> {
+    networks.iter()
+        .map(|network| {
+            let network = network.as_ref().trim();
+            // A bare address is a single-host network
+            network.parse::<IpNet>()
+                .or_else(|e| network.parse::<IpAddr>().map(IpNet::from).map_err(|_| e))
+        })
+        .collect()
+}
+
+/// Whether a proxy at this address could reach internal services.
+///
+/// Covers unspecified, loopback, private, shared (CGNAT), link-local (including
+/// the `169.254.169.254` cloud metadata endpoint), broadcast and multicast
+/// addresses, and their IPv6 counterparts such as unique local `fc00::/7`.
+/// IPv6 addresses embedding an IPv4 address (mapped, compatible, NAT64 and 6to4)
+/// are checked by their IPv4 address.
+pub fn is_unsafe_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_unsafe_ipv4(ip),
+        IpAddr::V6(ip) => is_unsafe_ipv6(ip) || embedded_ipv4(ip).is_some_and(is_unsafe_ipv4),
+    }
+}
+
+/// The IPv4 address embedded in an IPv6 address, if any.
+fn embedded_ipv4(ip: Ipv6Addr) -> Option<Ipv4Addr> {
+    let segments = ip.segments();
+    let [.., a, b, c, d] = ip.octets();
+    match segments {
+        // IPv4-mapped ::ffff:a.b.c.d and IPv4-compatible ::a.b.c.d
+        [0, 0, 0, 0, 0, 0xffff | 0, ..] => Some(Ipv4Addr::new(a, b, c, d)),
+        // NAT64 well-known prefix 64:ff9b::/96
+        [0x64, 0xff9b, 0, 0, 0, 0, ..] => Some(Ipv4Addr::new(a, b, c, d)),
+        // 6to4 2002:a.b.c.d::/48
+        [0x2002, high, low, ..] => Some(Ipv4Addr::from(((high as u32) << 16) | low as u32)),
+        _ => None,
+    }
+}
+
+fn is_unsafe_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        // Shared address space 100.64.0.0/10, home of some metadata endpoints
+        || (a == 100 && (64..128).contains(&b))
+        // "This network" 0.0.0.0/8
+        || a == 0
+}
+
+fn is_unsafe_ipv6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        // Unique local fc00::/7
+        || (first & 0xfe00) == 0xfc00
+        // Link-local fe80::/10
+        || (first & 0xffc0) == 0xfe80
+        // Local-use NAT64 64:ff9b:1::/48, the embedded address depends on the prefix length
+        || ip.segments()[..3] == [0x64, 0xff9b, 1]
+}
+
+/// DNS resolver that drops unsafe addresses of a proxy host.
+///
+/// Checking a hostname once when it joins the pool is not enough, it is resolved
+/// again on every connection and could then point at an internal service. Other
+/// names, such as request targets resolved locally for SOCKS, are left alone.
+pub(crate) struct SafeResolver {
+    host: String,
+}
+
+impl SafeResolver {
+    /// Create a resolver guarding the given proxy host.
+    pub(crate) fn new(host: &str) -> Self {
+        Self { host: host.to_string() }
+    }
+}
+
+impl Resolve for SafeResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let guarded = name.as_str().eq_ignore_ascii_case(&self.host);
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0)).await?
+                .filter(|addr| !guarded || !is_unsafe_address(addr.ip()))
+                .collect();
+            if guarded && addrs.is_empty() {
+                let message = format!("proxy host {} resolves to an unsafe address", name.as_str());
+                return Err(io::Error::new(io::ErrorKind::PermissionDenied, message).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proxy::ProxyUrl;
+
+    fn entry(url: &str) -> ProxyEntry {
+        ProxyEntry::new(ProxyUrl::parse_with_default(url, ProxyScheme::Http).unwrap())
+    }
+
+    #[test]
+    fn unsafe_addresses() {
+        let cases = [
+            ("127.0.0.1", true),
+            ("127.255.0.1", true),
+            ("10.1.2.3", true),
+            ("172.16.0.1", true),
+            ("192.168.1.1", true),
+            ("169.254.169.254", true),
+            ("100.64.0.1", true),
+            ("100.127.255.254", true),
+            ("0.0.0.0", true),
+            ("0.1.2.3", true),
+            ("255.255.255.255", true),
+            ("224.0.0.1", true),
+            ("::", true),
+            ("::1", true),
+            ("::ffff:127.0.0.1", true),
+            ("::127.0.0.1", true),
+            ("64:ff9b::a9fe:a9fe", true),
+            ("64:ff9b:1::1", true),
+            ("2002:7f00:1::", true),
+            ("2002:a9fe:a9fe::1", true),
+            ("fd00:ec2::254", true),
+            ("fc00::1", true),
+            ("fe80::1", true),
+            ("ff02::1", true),
+            ("8.8.8.8", false),
+            ("1.1.1.1", false),
+            ("100.63.255.255", false),
+            ("100.128.0.1", false),
+            ("203.0.113.10", false),
+            ("::ffff:8.8.8.8", false),
+            ("64:ff9b::808:808", false),
+            ("2002:808:808::1", false),
+            ("2001:4860:4860::8888", false),
+            ("2606:4700:4700::1111", false),
+        ];
+        for (address, expected) in cases {
+            let ip: IpAddr = address.parse().unwrap();
+            assert_eq!(is_unsafe_address(ip), expected, "{}", address);
+        }
+    }
+
+    #[test]
+    fn check_rules() {
+        let filter = ProxyFilter::new()
+            .allow_cidrs(vec!["203.0.113.0/24", "2001:db8::/32", "198.51.100.7"])
+            .unwrap()
+            .deny_cidrs(vec!["203.0.113.128/25"])
+            .unwrap()
+            .allow_network("192.0.2.0/24".parse().unwrap())
+            .allow_ports(1080..=1090)
+            .allow_ports(3128..=3128)
+            .allow_schemes([ProxyScheme::Http, ProxyScheme::Socks5])
+            .predicate(|entry| entry.url.port() != 1089);
+        let cases = [
+            ("socks5://203.0.113.10:1080", Ok(())),
+            ("http://[2001:db8::1]:3128", Ok(())),
+            ("http://198.51.100.7:1090", Ok(())),
+            ("http://192.0.2.1:1085", Ok(())),
+            ("http://203.0.113.200:1080", Err(FilterRejection::DeniedNetwork)),
+            ("http://198.51.100.8:1080", Err(FilterRejection::NotAllowedNetwork)),
+            ("http://8.8.8.8:1080", Err(FilterRejection::NotAllowedNetwork)),
+            ("http://proxy.example.com:1080", Err(FilterRejection::NotAllowedNetwork)),
+            ("http://203.0.113.10:1091", Err(FilterRejection::Port)),
+            ("http://203.0.113.10:3129", Err(FilterRejection::Port)),
+            ("https://203.0.113.10:1080", Err(FilterRejection::Scheme)),
+            ("socks4://203.0.113.10:1080", Err(FilterRejection::Scheme)),
+            ("http://203.0.113.10:1089", Err(FilterRejection::Predicate)),
+        ];
+        for (url, expected) in cases {
+            assert_eq!(filter.check(&entry(url)), expected, "{}", url);
+        }
+    }
+
+    #[test]
+    fn check_without_allow_list() {
+        let filter = ProxyFilter::new().deny_cidrs(vec!["10.0.0.0/8"]).unwrap();
+        assert_eq!(filter.check(&entry("http://proxy.example.com:80")), Ok(()));
+        assert_eq!(filter.check(&entry("http://8.8.8.8:80")), Ok(()));
+        assert_eq!(filter.check(&entry("http://10.1.2.3:80")), Err(FilterRejection::DeniedNetwork));
+        assert_eq!(ProxyFilter::new().check(&entry("socks4://10.1.2.3:1")), Ok(()));
+    }
+
+    #[test]
+    fn invalid_cidrs() {
+        assert!(ProxyFilter::new().allow_cidrs(vec!["203.0.113.0/24", "not-a-network"]).is_err());
+        assert!(ProxyFilter::new().deny_cidrs(vec!["10.0.0.0/33"]).is_err());
+        assert!(ProxyFilter::new().allow_cidrs(vec![" 10.0.0.1 "]).is_ok());
+    }
+}
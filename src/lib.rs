@@ -1,19 +1,26 @@
 //! # reqwest-proxy-pool
 //!
-//! A SOCKS5 proxy pool middleware for reqwest.
+//! A proxy pool middleware for reqwest.
 //!
 //! This library provides a middleware for reqwest that automatically manages a pool of
-//! SOCKS5 proxies, testing their health, and using them for requests with automatic retries.
+//! HTTP(S) and SOCKS proxies, testing their health, and using them for requests with automatic retries.
 
 pub mod config;
 pub mod error;
+pub mod filter;
 pub mod middleware;
 pub mod pool;
 pub mod proxy;
+pub mod source;
 mod utils;
 
-pub use config::{ProxyPoolConfig, ProxyPoolConfigBuilder, ProxySelectionStrategy};
-pub use error::NoProxyAvailable;
+pub use config::{MinAvailablePolicy, ProxyPoolConfig, ProxyPoolConfigBuilder, ProxySelectionStrategy};
+pub use error::{InvalidProxyUrl, NoProxyAvailable, ProxyPoolError, SourceError};
+pub use filter::{FilterRejection, ProxyFilter};
 pub use middleware::ProxyPoolMiddleware;
-pub use pool::ProxyPool;
-pub use proxy::{Proxy, ProxyStatus};
+pub use pool::{PoolEvent, ProxyPool};
+pub use proxy::{Proxy, ProxyCredentials, ProxyEntry, ProxyMetadata, ProxyScheme, ProxyStatus, ProxyUrl};
+pub use source::{
+    CsvOptions, EnvSource, FetchAuth, FetchOptions, FetchedList, FileSource, IngestionSummary, JsonFieldMapping, ProxySource, RetryPolicy, RowRejection, SourceFormat,
+    SourceOutcome, SourceStats, StaticSource, StdinSource, SystemProxySource, UrlSource,
+};
@@ -33,7 +33,7 @@ impl ProxyPoolMiddleware {
                 Ok(Self { pool })
             }
             Err(e) => {
-                Err(Error::Reqwest(e))
+                Err(Error::Middleware(anyhow!(e)))
             }
         }
     }
@@ -84,7 +84,7 @@ impl Middleware for ProxyPoolMiddleware {
                     };
                     
                     // Build a new client with the proxy
-                    let client = match reqwest::Client::builder()
+                    let client = match self.pool.client_builder(&proxy)
                         .proxy(reqwest_proxy)
                         .timeout(self.pool.config.health_check_timeout)
                         .build() {
@@ -1,133 +1,1063 @@
 //! Core proxy pool implementation.
 
-use crate::config::{ProxyPoolConfig, ProxySelectionStrategy};
-use crate::error::NoProxyAvailable;
-use crate::proxy::{Proxy, ProxyStatus};
-use crate::utils;
+use crate::config::{MinAvailablePolicy, ProxyPoolConfig, ProxySelectionStrategy};
+use crate::error::{NoProxyAvailable, ProxyPoolError, SourceError};
+use crate::filter::{self, FilterRejection, SafeResolver};
+use crate::proxy::{Proxy, ProxyEntry, ProxyScheme, ProxyStatus, ProxyUrl};
+use crate::source::{IngestionSummary, ProxySource, SourceOutcome, SourceStats};
 
+use arc_swap::ArcSwap;
 use futures::future;
-use log::{info, warn};
+use indexmap::IndexMap;
+use log::{debug, info, warn};
 use parking_lot::{Mutex, RwLock};
 use rand::Rng;
-use std::collections::HashSet;
+use std::collections::{hash_map, BTreeMap, HashMap, HashSet};
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+use tokio::net;
+use tokio::sync::{broadcast, Notify};
+use tokio::task::JoinHandle;
 use tokio::time::{self};
+use tokio_util::sync::CancellationToken;
+
+/// Source name of proxies added with `ProxyPool::add_proxy`.
+const MANUAL_SOURCE: &str = "manual";
+
+/// Upper bound of the replenish cooldown while replenishments bring no new proxies.
+const MAX_REPLENISH_COOLDOWN: Duration = Duration::from_secs(3600);
+
+/// Interval between rounds while `ProxyPool::new` waits for `min_available_proxies`.
+const STARTUP_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Notable changes of the pool, see `ProxyPool::subscribe`.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum PoolEvent {
+    /// Fewer than `min_available_proxies` proxies are available, a replenishment was triggered.
+    BelowMinimum {
+        /// Number of available proxies.
+        available: usize,
+        /// Configured minimum.
+        required: usize,
+    },
+    /// At least `min_available_proxies` proxies are available again.
+    MinimumRestored {
+        /// Number of available proxies.
+        available: usize,
+        /// Configured minimum.
+        required: usize,
+    },
+}
+
+/// Pool members indexed by URL, in insertion order.
+type ProxyMap = IndexMap<ProxyUrl, Arc<Proxy>>;
+
+/// Precomputed selection state, rebuilt when membership, status or bans change.
+#[derive(Default)]
+struct Snapshot {
+    /// Healthy, unbanned proxies with a positive weight.
+    proxies: Vec<Arc<Proxy>>,
+    /// Running sum of the weights, for weighted random selection.
+    cumulative_weights: Vec<f64>,
+    /// Index of the proxy with the fastest response time.
+    fastest: usize,
+    /// When the earliest ban among the pool members ends and the snapshot goes stale.
+    expires_at: Option<Instant>,
+}
+
+/// A deduplicated entry together with the sources that listed it.
+#[derive(Clone)]
+struct FetchedProxy {
+    entry: ProxyEntry,
+    sources: Vec<String>,
+    weight: f64,
+    resolved_from: Option<String>,
+}
+
+/// Whether a weight can take part in weighted selection.
+fn is_valid_weight(weight: f64) -> bool {
+    weight.is_finite() && weight >= 0.0
+}
+
+/// The weight of a source, negative and non-finite weights fall back to 1.0.
+fn source_weight(source: &dyn ProxySource) -> f64 {
+    let weight = source.weight();
+    if is_valid_weight(weight) {
+        weight
+    } else {
+        warn!("Ignoring invalid weight {} of source {}", weight, source.name());
+        1.0
+    }
+}
+
+/// The weight of an entry, falling back to the weight of its source.
+fn entry_weight(entry: &ProxyEntry, source_weight: f64) -> f64 {
+    entry.weight.filter(|&weight| is_valid_weight(weight)).unwrap_or(source_weight)
+}
+
+/// Whether a ban ending at `until` (`None` for never) is still in effect.
+fn ban_active(until: Option<Instant>, now: Instant) -> bool {
+    until.is_none_or(|until| until > now)
+}
+
+/// Whether `resolve_hostnames` replaces a proxy by its addresses.
+///
+/// TLS proxies keep their hostname, a certificate does not match an address.
+fn is_resolvable(url: &ProxyUrl) -> bool {
+    url.ip().is_none() && url.scheme() != ProxyScheme::Https
+}
 
 /// A pool of proxies that can be used for HTTP requests.
 pub struct ProxyPool {
     /// All proxies in the pool.
-    proxies: RwLock<Vec<Proxy>>,
+    ///
+    /// Health state and counters live in atomics inside each proxy, so reporting
+    /// outcomes only takes the read lock.
+    proxies: RwLock<ProxyMap>,
     /// Configuration for the pool.
     pub config: ProxyPoolConfig,
     /// Used for round-robin proxy selection.
-    last_proxy_index: Mutex<usize>,
+    last_proxy_index: AtomicUsize,
+    /// Selectable proxies, swapped atomically so `get_proxy` takes no lock.
+    snapshot: ArcSwap<Snapshot>,
+    /// Serializes snapshot rebuilds so a stale rebuild never replaces a newer one.
+    snapshot_lock: Mutex<()>,
+    /// Outcome of the most recent fetch of each source.
+    source_outcomes: RwLock<Vec<SourceOutcome>>,
+    /// Summary of the most recent fetch of all sources.
+    ingestion_summary: RwLock<Option<IngestionSummary>>,
+    /// Hostname entries from the latest fetch, re-resolved on `dns_refresh_interval`.
+    hostname_entries: RwLock<Vec<FetchedProxy>>,
+    /// Proxies added at runtime, listed as the `"manual"` source on every fetch.
+    manual_entries: RwLock<HashMap<ProxyUrl, ProxyEntry>>,
+    /// Banned proxies and when their ban ends, `None` for a permanent ban.
+    bans: RwLock<HashMap<ProxyUrl, Option<Instant>>>,
+    /// Number of entries rejected by the configured filter, per reason.
+    filter_rejections: Mutex<BTreeMap<FilterRejection, usize>>,
+    /// Whether fewer than `min_available_proxies` proxies are available.
+    below_minimum: AtomicBool,
+    /// Whether the minimum is enforced, which starts with the initial health check.
+    enforce_minimum: AtomicBool,
+    /// Wakes the replenish task when the pool falls below `min_available_proxies`.
+    replenish: Arc<Notify>,
+    /// Consecutive replenishments that did not raise the number of available proxies.
+    replenish_failures: Arc<AtomicU32>,
+    /// Whether the last replenishment re-fetched sources without finding new proxies.
+    replenish_found_nothing: AtomicBool,
+    /// Sender for `PoolEvent`s.
+    events: broadcast::Sender<PoolEvent>,
+    /// Stops the background tasks.
+    cancel: CancellationToken,
+    /// Handles of the background tasks.
+    tasks: Mutex<Vec<JoinHandle<()>>>,
 }
 
 impl ProxyPool {
     /// Create a new proxy pool with the given configuration.
     /// This will fetch proxies from sources and perform health checks synchronously.
-    pub async fn new(config: ProxyPoolConfig) -> Result<Arc<Self>, reqwest::Error> {
+    ///
+    /// With `strict_sources` enabled this fails if any source is unreachable or yields no proxies.
+    pub async fn new(config: ProxyPoolConfig) -> Result<Arc<Self>, ProxyPoolError> {
         let pool = Arc::new(Self {
-            proxies: RwLock::new(Vec::new()),
+            proxies: RwLock::new(IndexMap::new()),
             config,
-            last_proxy_index: Mutex::new(0),
+            last_proxy_index: AtomicUsize::new(0),
+            snapshot: ArcSwap::from_pointee(Snapshot::default()),
+            snapshot_lock: Mutex::new(()),
+            source_outcomes: RwLock::new(Vec::new()),
+            ingestion_summary: RwLock::new(None),
+            hostname_entries: RwLock::new(Vec::new()),
+            manual_entries: RwLock::new(HashMap::new()),
+            bans: RwLock::new(HashMap::new()),
+            filter_rejections: Mutex::new(BTreeMap::new()),
+            below_minimum: AtomicBool::new(false),
+            enforce_minimum: AtomicBool::new(false),
+            replenish: Arc::new(Notify::new()),
+            replenish_failures: Arc::new(AtomicU32::new(0)),
+            replenish_found_nothing: AtomicBool::new(false),
+            events: broadcast::channel(16).0,
+            cancel: CancellationToken::new(),
+            tasks: Mutex::new(Vec::new()),
         });
         
         // Initialize proxies from sources
         pool.initialize_proxies().await?;
         
-        // Perform initial health check synchronously
+        // Perform initial health check synchronously, until now every proxy was unchecked
         info!("Starting synchronous initial health check");
+        pool.enforce_minimum.store(true, Ordering::Relaxed);
         pool.check_all_proxies().await;
+        pool.ensure_min_available().await?;
         
         // Display initial stats
         let (total, healthy) = pool.get_stats();
         info!("Initial proxy pool status: {}/{} healthy proxies", healthy, total);
         
         // Start background health check task
-        let pool_clone = Arc::clone(&pool);
-        tokio::spawn(async move {
+        pool.spawn_task(Self::every(pool.config.health_check_interval), |pool| async move {
+            pool.check_all_proxies().await;
+            
+            let (total, healthy) = pool.get_stats();
+            info!("Proxy pool status update: {}/{} healthy proxies", healthy, total);
+        });
+        
+        // Start background task replenishing a pool below the minimum
+        let replenish = Arc::clone(&pool.replenish);
+        let failures = Arc::clone(&pool.replenish_failures);
+        let cooldown = pool.config.replenish_cooldown;
+        let mut first = true;
+        pool.spawn_task(
+            move || {
+                let replenish = Arc::clone(&replenish);
+                let pause = if std::mem::take(&mut first) {
+                    Duration::ZERO
+                } else {
+                    // Back off while replenishing does not help
+                    let factor = 1u32 << failures.load(Ordering::Relaxed).min(16);
+                    cooldown.saturating_mul(factor).min(MAX_REPLENISH_COOLDOWN.max(cooldown))
+                };
+                async move {
+                    time::sleep(pause).await;
+                    replenish.notified().await;
+                }
+            },
+            |pool| async move { pool.replenish_proxies().await },
+        );
+        
+        // Start background source refresh task
+        if let Some(interval) = pool.config.source_refresh_interval {
+            pool.spawn_task(Self::every(interval), |pool| async move { pool.refresh_sources().await });
+        }
+        
+        // Start background file watch tasks
+        for source in &pool.config.sources {
+            if let Some(interval) = source.watch_interval() {
+                let source = Arc::clone(source);
+                pool.spawn_task(Self::every(interval), move |pool| {
+                    let source = Arc::clone(&source);
+                    async move {
+                        if source.has_changed().await {
+                            pool.reload_source(&source).await;
+                        }
+                    }
+                });
+            }
+        }
+        
+        // Start background DNS refresh task
+        if let (true, Some(interval)) = (pool.config.resolve_hostnames, pool.config.dns_refresh_interval) {
+            pool.spawn_task(Self::every(interval), |pool| async move { pool.refresh_dns().await });
+        }
+        
+        Ok(pool)
+    }
+    
+    /// Spawn a background task that runs `tick` each time `wait` completes.
+    ///
+    /// The task holds the pool only while `tick` runs, and stops when the pool is
+    /// shut down or dropped.
+    fn spawn_task<W, WF, T, TF>(self: &Arc<Self>, mut wait: W, tick: T)
+    where
+        W: FnMut() -> WF + Send + 'static,
+        WF: Future<Output = ()> + Send,
+        T: Fn(Arc<Self>) -> TF + Send + 'static,
+        TF: Future<Output = ()> + Send,
+    {
+        let pool = Arc::downgrade(self);
+        let cancel = self.cancel.clone();
+        
+        let handle = tokio::spawn(async move {
             loop {
-                time::sleep(pool_clone.config.health_check_interval).await;
-                pool_clone.check_all_proxies().await;
-                
-                let (total, healthy) = pool_clone.get_stats();
-                info!("Proxy pool status update: {}/{} healthy proxies", healthy, total);
+                tokio::select! {
+                    _ = cancel.cancelled() => break,
+                    _ = wait() => {}
+                }
+                let Some(pool) = pool.upgrade() else {
+                    break;
+                };
+                tokio::select! {
+                    _ = cancel.cancelled() => break,
+                    _ = tick(pool) => {}
+                }
             }
         });
         
-        Ok(pool)
+        self.tasks.lock().push(handle);
+    }
+    
+    /// A `wait` for `spawn_task` that sleeps for the interval.
+    fn every(interval: Duration) -> impl FnMut() -> time::Sleep + Send + 'static {
+        move || time::sleep(interval)
+    }
+    
+    /// Stop the background tasks and wait for them to finish.
+    ///
+    /// Health checks, source refreshes, file watches, DNS refreshes and replenishment
+    /// stop and an operation in progress is abandoned. The pool can still hand out
+    /// proxies. Dropping the pool stops the tasks as well, without waiting.
+    pub async fn shutdown(&self) {
+        info!("Shutting down proxy pool");
+        self.cancel.cancel();
+        
+        let tasks = std::mem::take(&mut *self.tasks.lock());
+        for task in tasks {
+            if let Err(e) = task.await {
+                warn!("Background task failed: {}", e);
+            }
+        }
+    }
+    
+    /// Apply `min_available_policy` after the initial health check.
+    async fn ensure_min_available(&self) -> Result<(), ProxyPoolError> {
+        let required = self.config.min_available_proxies;
+        let deadline = match self.config.min_available_policy {
+            MinAvailablePolicy::Continue => return Ok(()),
+            // A timeout too long to represent, such as `Duration::MAX`, waits forever
+            MinAvailablePolicy::Wait(timeout) => Instant::now().checked_add(timeout),
+            MinAvailablePolicy::Fail => Some(Instant::now()),
+        };
+        
+        loop {
+            let available = self.available_count();
+            if available >= required {
+                return Ok(());
+            }
+            
+            let remaining = deadline.map_or(Duration::MAX, |at| at.saturating_duration_since(Instant::now()));
+            if remaining.is_zero() {
+                return Err(ProxyPoolError::NotEnoughProxies { available, required });
+            }
+            
+            info!("Waiting for {} available proxies, {} so far", required, available);
+            time::sleep(remaining.min(STARTUP_RETRY_INTERVAL)).await;
+            self.replenish_proxies().await;
+        }
+    }
+    
+    /// Re-fetch sources and re-check all proxies if the pool is below `min_available_proxies`.
+    ///
+    /// After a re-fetch that found no new proxies the next replenishment only
+    /// re-checks the known proxies, sparing the sources.
+    async fn replenish_proxies(&self) {
+        let required = self.config.min_available_proxies;
+        let available = self.available_count();
+        if available >= required {
+            self.replenish_failures.store(0, Ordering::Relaxed);
+            return;
+        }
+        
+        if self.replenish_found_nothing.swap(false, Ordering::Relaxed) {
+            info!("Replenishing proxy pool below {} available proxies, re-checking known proxies", required);
+        } else {
+            info!("Replenishing proxy pool below {} available proxies", required);
+            let added = self.update_from_sources().await;
+            self.replenish_found_nothing.store(added == 0, Ordering::Relaxed);
+        }
+        self.check_all_proxies().await;
+        
+        if self.available_count() > available {
+            self.replenish_failures.store(0, Ordering::Relaxed);
+        } else {
+            self.replenish_failures.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    
+    /// Number of proxies currently available for selection.
+    fn available_count(&self) -> usize {
+        self.snapshot.load().proxies.len()
+    }
+    
+    /// Subscribe to notable changes of the pool, such as falling below `min_available_proxies`.
+    pub fn subscribe(&self) -> broadcast::Receiver<PoolEvent> {
+        self.events.subscribe()
     }
     
     /// Initialize the proxy pool by fetching proxies from all configured sources.
-    async fn initialize_proxies(&self) -> Result<(), reqwest::Error> {
+    async fn initialize_proxies(&self) -> Result<(), ProxyPoolError> {
         info!("Initializing proxy pool from {} sources", self.config.sources.len());
         
-        let mut all_proxies = HashSet::new();
+        let all_proxies = self.fetch_sources().await;
+        self.remember_hostnames(&all_proxies);
+        let all_proxies = self.resolve_hostnames(all_proxies).await;
+        let all_proxies = self.filter_entries(all_proxies).await;
+        info!("Found {} unique proxies before health check", all_proxies.len());
         
-        // Fetch proxies from each source
-        for source in &self.config.sources {
-            match utils::fetch_proxies_from_source(source).await {
-                Ok(source_proxies) => {
-                    info!("Fetched {} proxies from {}", source_proxies.len(), source);
-                    all_proxies.extend(source_proxies);
-                }
-                Err(e) => {
-                    warn!("Failed to fetch proxies from {}: {}", source, e);
+        if self.config.strict_sources {
+            let outcomes = self.source_outcomes.read();
+            if let Some((name, error)) = outcomes.iter().find_map(|o| o.error.as_ref().map(|e| (&o.source, e))) {
+                return Err(ProxyPoolError::Source {
+                    name: name.clone(),
+                    error: Arc::clone(error),
+                });
+            }
+        }
+        
+        // Add proxies to the pool
+        self.merge_proxies(all_proxies);
+        self.rebuild_snapshot();
+        
+        Ok(())
+    }
+    
+    /// Re-fetch all sources and merge the result into the pool.
+    ///
+    /// Newly seen proxies are added and health checked, known proxies keep their
+    /// statistics, and proxies absent from every source for longer than
+    /// `source_retirement_grace` are retired.
+    pub async fn refresh_sources(&self) {
+        self.update_from_sources().await;
+    }
+    
+    /// Re-fetch all sources, merge the result and return the number of new proxies.
+    async fn update_from_sources(&self) -> usize {
+        info!("Refreshing proxy pool from {} sources", self.config.sources.len());
+        
+        let all_proxies = self.fetch_sources().await;
+        self.remember_hostnames(&all_proxies);
+        let all_proxies = self.resolve_hostnames(all_proxies).await;
+        let all_proxies = self.filter_entries(all_proxies).await;
+        let added = self.merge_proxies(all_proxies);
+        let retired = self.retire_absent_proxies();
+        self.rebuild_snapshot();
+        
+        info!("Source refresh completed: {} added, {} retired", added.len(), retired);
+        
+        let count = added.len();
+        if !added.is_empty() {
+            self.check_proxies(added).await;
+        }
+        count
+    }
+    
+    /// Fetch proxies from all configured sources concurrently, deduplicated by URL.
+    ///
+    /// The outcome of each source is recorded and available via `source_outcomes`,
+    /// a summary of the whole fetch via `ingestion_summary`.
+    async fn fetch_sources(&self) -> HashMap<ProxyUrl, FetchedProxy> {
+        let started = Instant::now();
+        let results = future::join_all(self.config.sources.iter()
+            .map(|source| self.fetch_source(source))).await;
+        
+        // Merge in configuration order so deduplication does not depend on timing
+        let mut all_proxies = HashMap::new();
+        let mut outcomes = Vec::with_capacity(results.len());
+        for (source, (source_proxies, outcome)) in self.config.sources.iter().zip(results) {
+            let source_weight = source_weight(source.as_ref());
+            let mut conflicting_credentials = 0;
+            for entry in source_proxies {
+                // A weight annotated on the entry overrides the source weight
+                let weight = entry_weight(&entry, source_weight);
+                let fetched = match all_proxies.entry(entry.url.clone()) {
+                    hash_map::Entry::Occupied(occupied) => {
+                        let known: &FetchedProxy = occupied.get();
+                        if known.entry.credentials != entry.credentials {
+                            debug!("Ignoring other credentials for {} from {}", entry.url, outcome.source);
+                            conflicting_credentials += 1;
+                        }
+                        occupied.into_mut()
+                    }
+                    hash_map::Entry::Vacant(vacant) => vacant.insert(FetchedProxy {
+                        entry,
+                        sources: Vec::new(),
+                        weight: 0.0,
+                        resolved_from: None,
+                    }),
+                };
+                if !fetched.sources.contains(&outcome.source) {
+                    fetched.sources.push(outcome.source.clone());
                 }
+                fetched.weight = fetched.weight.max(weight);
+            }
+            if conflicting_credentials > 0 {
+                // Credentials are not part of a proxy's identity, the first ones listed win
+                warn!("{} proxies from {} were already listed with other credentials, keeping the first credentials",
+                    conflicting_credentials, outcome.source);
             }
+            outcomes.push(outcome);
         }
         
-        info!("Found {} unique proxies before health check", all_proxies.len());
+        // Proxies added at runtime are listed on every fetch
+        for entry in self.manual_entries.read().values() {
+            let weight = entry_weight(entry, 1.0);
+            let fetched = all_proxies.entry(entry.url.clone()).or_insert_with(|| FetchedProxy {
+                entry: entry.clone(),
+                sources: Vec::new(),
+                weight: 0.0,
+                resolved_from: None,
+            });
+            fetched.sources.push(MANUAL_SOURCE.to_string());
+            fetched.weight = fetched.weight.max(weight);
+        }
         
-        // Add proxies to the pool
+        let summary = IngestionSummary {
+            sources: outcomes.len(),
+            failed: outcomes.iter().filter(|o| !o.is_ok()).count(),
+            fetched: outcomes.iter().map(|o| o.fetched).sum(),
+            unique: all_proxies.len(),
+            duration: started.elapsed(),
+        };
+        info!("Fetched {} sources in {:?}: {} failed, {} proxies, {} unique",
+            summary.sources, summary.duration, summary.failed, summary.fetched, summary.unique);
+        
+        *self.source_outcomes.write() = outcomes;
+        *self.ingestion_summary.write() = Some(summary);
+        all_proxies
+    }
+    
+    /// Fetch a single source under its retry policy, returning its entries and the outcome of the fetch.
+    async fn fetch_source(&self, source: &Arc<dyn ProxySource>) -> (Vec<ProxyEntry>, SourceOutcome) {
+        let policy = source.retry_policy().unwrap_or(self.config.source_retry_policy);
+        let started = Instant::now();
+        let mut attempts = 0;
+        
+        let mut rejected_rows = BTreeMap::new();
+        
+        let result = loop {
+            attempts += 1;
+            let result = match time::timeout(policy.timeout, source.fetch_list()).await {
+                Ok(Ok(list)) => {
+                    // Keep the rejected rows even when they leave the list empty
+                    rejected_rows = list.rejected_rows;
+                    if list.entries.is_empty() {
+                        Err(SourceError::Empty)
+                    } else {
+                        Ok(list.entries)
+                    }
+                }
+                Ok(Err(e)) => Err(e),
+                Err(_) => Err(SourceError::Timeout(policy.timeout)),
+            };
+            match result {
+                Err(e) if e.is_transient() && attempts <= policy.max_retries => {
+                    let delay = policy.delay(attempts);
+                    warn!("Failed to fetch proxies from {} (attempt {}): {}, retrying in {:?}",
+                        source.name(), attempts, e, delay);
+                    time::sleep(delay).await;
+                }
+                result => break result,
+            }
+        };
+        
+        let duration = started.elapsed();
+        match result {
+            Ok(source_proxies) => {
+                info!("Fetched {} proxies from {} in {:?}", source_proxies.len(), source.name(), duration);
+                let outcome = SourceOutcome {
+                    source: source.name(),
+                    fetched: source_proxies.len(),
+                    error: None,
+                    fetched_at: Instant::now(),
+                    duration,
+                    attempts,
+                    rejected_rows,
+                };
+                (source_proxies, outcome)
+            }
+            Err(e) => {
+                warn!("Failed to fetch proxies from {} after {} attempts in {:?}: {}", source.name(), attempts, duration, e);
+                let outcome = SourceOutcome {
+                    source: source.name(),
+                    fetched: 0,
+                    error: Some(Arc::new(e)),
+                    fetched_at: Instant::now(),
+                    duration,
+                    attempts,
+                    rejected_rows,
+                };
+                (Vec::new(), outcome)
+            }
+        }
+    }
+    
+    /// Re-fetch a single source and apply the difference to the pool.
+    ///
+    /// Proxies the source no longer lists are dropped unless another source still
+    /// lists them, requests already using them finish normally. Unchanged proxies
+    /// keep their statistics. If the source cannot be read the pool is left as is.
+    async fn reload_source(&self, source: &Arc<dyn ProxySource>) {
+        let name = source.name();
+        info!("Reloading proxies from {}", name);
+        
+        let (entries, outcome) = self.fetch_source(source).await;
+        let failed = outcome.error.as_deref().is_some_and(|e| !matches!(e, SourceError::Empty));
         {
+            let mut outcomes = self.source_outcomes.write();
+            match outcomes.iter_mut().find(|o| o.source == name) {
+                Some(existing) => *existing = outcome,
+                None => outcomes.push(outcome),
+            }
+        }
+        if failed {
+            return;
+        }
+        
+        let source_weight = source_weight(source.as_ref());
+        let fetched: HashMap<ProxyUrl, FetchedProxy> = entries.into_iter()
+            .map(|entry| (entry.url.clone(), FetchedProxy {
+                weight: entry_weight(&entry, source_weight),
+                entry,
+                sources: vec![name.clone()],
+                resolved_from: None,
+            }))
+            .collect();
+        let fetched = self.resolve_hostnames(fetched).await;
+        let fetched = self.filter_entries(fetched).await;
+        
+        let now = Instant::now();
+        let added;
+        let removed = {
+            let mut proxies = self.proxies.write();
+            
+            // Drop proxies this source no longer lists
+            let before = proxies.len();
+            proxies.retain(|url, p| {
+                if fetched.contains_key(url) || !p.sources.contains(&name) {
+                    return true;
+                }
+                let p = Arc::make_mut(p);
+                p.sources.retain(|s| s != &name);
+                !p.sources.is_empty()
+            });
+            let removed = before - proxies.len();
+            
+            added = self.upsert_proxies(&mut proxies, &name, fetched, now);
+            
+            removed
+        };
+        
+        self.rebuild_snapshot();
+        
+        info!("Reloaded {}: {} added, {} removed", name, added.len(), removed);
+        
+        if !added.is_empty() {
+            self.check_proxies(added).await;
+        }
+    }
+    
+    /// Add a proxy to the pool at runtime and health check it.
+    ///
+    /// The proxy is listed by a source named `"manual"` and stays in the pool across
+    /// source refreshes until `remove_proxy` is called. Returns `false` if the entry
+    /// is rejected by the safety policy or the configured filter.
+    pub async fn add_proxy(&self, entry: ProxyEntry) -> bool {
+        let fetched = HashMap::from([(entry.url.clone(), FetchedProxy {
+            weight: entry_weight(&entry, 1.0),
+            entry: entry.clone(),
+            sources: vec![MANUAL_SOURCE.to_string()],
+            resolved_from: None,
+        })]);
+        let fetched = self.resolve_hostnames(fetched).await;
+        let fetched = self.filter_entries(fetched).await;
+        if fetched.is_empty() {
+            return false;
+        }
+        
+        self.manual_entries.write().insert(entry.url.clone(), entry);
+        let added = {
+            let mut proxies = self.proxies.write();
+            self.upsert_proxies(&mut proxies, MANUAL_SOURCE, fetched, Instant::now())
+        };
+        
+        info!("Added {} proxies manually", added.len());
+        
+        if !added.is_empty() {
+            self.check_proxies(added).await;
+        }
+        true
+    }
+    
+    /// Remove a proxy from the pool, returning whether it was a member.
+    ///
+    /// Requests already using the proxy finish normally, their outcome is no longer
+    /// recorded. A source that still lists the proxy adds it again on its next fetch,
+    /// use `ban_proxy` to keep it out. For a hostname added with `resolve_hostnames`
+    /// enabled, the addresses it resolved to are removed unless a source lists them.
+    pub fn remove_proxy(&self, url: &ProxyUrl) -> bool {
+        self.manual_entries.write().remove(url);
+        self.hostname_entries.write().retain(|fetched| fetched.entry.url != *url);
+        
+        let removed = {
+            let mut proxies = self.proxies.write();
+            let before = proxies.len();
+            proxies.retain(|member, proxy| {
+                let resolved_manually = proxy.resolved_from.as_deref() == Some(url.host())
+                    && member.scheme() == url.scheme()
+                    && member.port() == url.port()
+                    && proxy.sources.iter().all(|source| source == MANUAL_SOURCE);
+                member != url && !resolved_manually
+            });
+            before - proxies.len()
+        };
+        self.rebuild_snapshot();
+        
+        if removed > 0 {
+            info!("Removed {} proxies for {}", removed, url);
+        }
+        removed > 0
+    }
+    
+    /// Keep a proxy from being selected for the given duration.
+    ///
+    /// The proxy stays in the pool and keeps its statistics. Bans also apply to
+    /// proxies that are not (yet) in the pool. A duration too long to represent,
+    /// such as `Duration::MAX`, bans the proxy until `unban_proxy` is called.
+    pub fn ban_proxy(&self, url: &ProxyUrl, duration: Duration) {
+        info!("Banning proxy {} for {:?}", url, duration);
+        self.bans.write().insert(url.clone(), Instant::now().checked_add(duration));
+        self.rebuild_snapshot();
+    }
+    
+    /// Lift the ban of a proxy, returning whether it was banned.
+    pub fn unban_proxy(&self, url: &ProxyUrl) -> bool {
+        let unbanned = self.bans.write().remove(url).is_some_and(|until| ban_active(until, Instant::now()));
+        self.rebuild_snapshot();
+        if unbanned {
+            info!("Unbanned proxy {}", url);
+        }
+        unbanned
+    }
+    
+    /// Whether a proxy is currently banned.
+    pub fn is_banned(&self, url: &ProxyUrl) -> bool {
+        self.bans.read().get(url).is_some_and(|&until| ban_active(until, Instant::now()))
+    }
+    
+    /// Get the outcome of the most recent fetch of each source.
+    pub fn source_outcomes(&self) -> Vec<SourceOutcome> {
+        self.source_outcomes.read().clone()
+    }
+    
+    /// Get a summary of the most recent fetch of all sources, `None` before the first fetch.
+    pub fn ingestion_summary(&self) -> Option<IngestionSummary> {
+        self.ingestion_summary.read().clone()
+    }
+    
+    /// Get statistics about the proxies each source contributes to the pool.
+    pub fn source_stats(&self) -> Vec<SourceStats> {
+        let outcomes = self.source_outcomes.read();
+        let proxies = self.proxies.read();
+        
+        outcomes.iter()
+            .map(|outcome| {
+                let members: Vec<&Arc<Proxy>> = proxies.values()
+                    .filter(|p| p.sources.contains(&outcome.source))
+                    .collect();
+                let successes: usize = members.iter().map(|p| p.success_count()).sum();
+                let failures: usize = members.iter().map(|p| p.failure_count()).sum();
+                
+                SourceStats {
+                    source: outcome.source.clone(),
+                    fetched: outcome.fetched,
+                    unique: members.len(),
+                    healthy: members.iter().filter(|p| p.status() == ProxyStatus::Healthy).count(),
+                    success_rate: if successes + failures == 0 {
+                        0.0
+                    } else {
+                        successes as f64 / (successes + failures) as f64
+                    },
+                }
+            })
+            .collect()
+    }
+    
+    /// Remember the hostname entries of a full fetch so `refresh_dns` can re-resolve them.
+    fn remember_hostnames(&self, fetched: &HashMap<ProxyUrl, FetchedProxy>) {
+        if self.config.resolve_hostnames {
+            *self.hostname_entries.write() = fetched.iter()
+                .filter(|(url, _)| is_resolvable(url))
+                .map(|(_, fetched)| fetched.clone())
+                .collect();
+        }
+    }
+    
+    /// Replace hostname entries by one entry per resolved address, if enabled.
+    async fn resolve_hostnames(&self, fetched: HashMap<ProxyUrl, FetchedProxy>) -> HashMap<ProxyUrl, FetchedProxy> {
+        if !self.config.resolve_hostnames {
+            return fetched;
+        }
+        
+        let (hostnames, mut addresses): (HashMap<_, _>, HashMap<_, _>) = fetched.into_iter()
+            .partition(|(url, _)| is_resolvable(url));
+        let hostnames: Vec<FetchedProxy> = hostnames.into_values().collect();
+        
+        let (resolved, _) = Self::resolve_entries(hostnames).await;
+        for (url, fetched) in resolved {
+            addresses.entry(url).or_insert(fetched);
+        }
+        addresses
+    }
+    
+    /// Re-resolve hostname entries from the latest source fetch.
+    ///
+    /// Newly resolved addresses join the pool, addresses a hostname no longer
+    /// resolves to are removed, and unchanged addresses keep their statistics.
+    pub async fn refresh_dns(&self) {
+        let hostnames = self.hostname_entries.read().clone();
+        if hostnames.is_empty() {
+            return;
+        }
+        
+        let (resolved, resolved_hosts) = Self::resolve_entries(hostnames).await;
+        let resolved = self.filter_entries(resolved).await;
+        let current: HashSet<ProxyUrl> = resolved.keys().cloned().collect();
+        let added = self.merge_proxies(resolved);
+        
+        let removed = {
             let mut proxies = self.proxies.write();
-            for url in all_proxies {
-                proxies.push(Proxy::new(url, self.config.max_requests_per_second));
+            let before = proxies.len();
+            proxies.retain(|url, p| match &p.resolved_from {
+                Some(host) if resolved_hosts.contains(host) => current.contains(url),
+                _ => true,
+            });
+            before - proxies.len()
+        };
+        
+        self.rebuild_snapshot();
+        
+        info!("DNS refresh completed: {} added, {} removed", added.len(), removed);
+        
+        if !added.is_empty() {
+            self.check_proxies(added).await;
+        }
+    }
+    
+    /// Drop unsafe addresses and entries rejected by the configured filter, counting and logging them per reason.
+    ///
+    /// Unless `allow_unsafe_addresses` is set, hostnames are looked up and rejected
+    /// when any of their addresses is unsafe.
+    async fn filter_entries(&self, entries: HashMap<ProxyUrl, FetchedProxy>) -> HashMap<ProxyUrl, FetchedProxy> {
+        let unsafe_hosts = if self.config.allow_unsafe_addresses {
+            HashSet::new()
+        } else {
+            Self::unsafe_hostnames(entries.keys()).await
+        };
+        
+        let mut rejected: BTreeMap<FilterRejection, usize> = BTreeMap::new();
+        let entries: HashMap<ProxyUrl, FetchedProxy> = entries.into_iter()
+            .filter(|(url, fetched)| {
+                let unsafe_address = !self.config.allow_unsafe_addresses && match url.ip() {
+                    Some(ip) => filter::is_unsafe_address(ip),
+                    None => unsafe_hosts.contains(url.host()),
+                };
+                let result = if unsafe_address {
+                    Err(FilterRejection::UnsafeAddress)
+                } else {
+                    self.config.filter.check(&fetched.entry)
+                };
+                match result {
+                    Ok(()) => true,
+                    Err(reason) => {
+                        debug!("Filtered out proxy {}: {}", url, reason);
+                        *rejected.entry(reason).or_default() += 1;
+                        false
+                    }
+                }
+            })
+            .collect();
+        
+        if !rejected.is_empty() {
+            let mut totals = self.filter_rejections.lock();
+            for (reason, count) in rejected {
+                info!("Filtered out {} proxies: {}", count, reason);
+                *totals.entry(reason).or_default() += count;
             }
         }
         
-        Ok(())
+        entries
+    }
+    
+    /// Look up hostname proxies and return the hosts resolving to an unsafe address.
+    ///
+    /// Hosts that fail to resolve count as unsafe, they may resolve to an internal
+    /// address by the time a connection is made.
+    async fn unsafe_hostnames(urls: impl Iterator<Item = &ProxyUrl>) -> HashSet<String> {
+        let hosts: HashSet<(&str, u16)> = urls
+            .filter(|url| url.ip().is_none())
+            .map(|url| (url.host(), url.port()))
+            .collect();
+        let lookups = hosts.into_iter().map(|(host, port)| async move {
+            let unsafe_host = match net::lookup_host((host, port)).await {
+                Ok(mut addrs) => addrs.any(|addr| filter::is_unsafe_address(addr.ip())),
+                Err(e) => {
+                    debug!("Failed to resolve proxy host {}: {}", host, e);
+                    true
+                }
+            };
+            unsafe_host.then(|| host.to_string())
+        });
+        
+        future::join_all(lookups).await.into_iter().flatten().collect()
+    }
+    
+    /// Get the number of entries rejected by the configured filter since the pool was created, per reason.
+    pub fn filter_rejections(&self) -> BTreeMap<FilterRejection, usize> {
+        self.filter_rejections.lock().clone()
+    }
+    
+    /// Resolve hostname entries concurrently into one entry per address.
+    ///
+    /// Also returns the hostnames that resolved successfully.
+    async fn resolve_entries(hostnames: Vec<FetchedProxy>) -> (HashMap<ProxyUrl, FetchedProxy>, HashSet<String>) {
+        let lookups = hostnames.into_iter().map(|fetched| async move {
+            let url = &fetched.entry.url;
+            let result = net::lookup_host((url.host(), url.port())).await
+                .map(|addrs| addrs.collect::<Vec<_>>());
+            (fetched, result)
+        });
+        
+        let mut resolved = HashMap::new();
+        let mut resolved_hosts = HashSet::new();
+        
+        for (fetched, result) in future::join_all(lookups).await {
+            let host = fetched.entry.url.host().to_string();
+            match result {
+                Ok(addrs) => {
+                    for addr in addrs {
+                        let Ok(url) = ProxyUrl::new(fetched.entry.url.scheme(), &addr.ip().to_string(), addr.port()) else {
+                            continue;
+                        };
+                        let mut entry = fetched.clone();
+                        entry.entry.url = url.clone();
+                        entry.resolved_from = Some(host.clone());
+                        resolved.entry(url).or_insert(entry);
+                    }
+                    resolved_hosts.insert(host);
+                }
+                Err(e) => warn!("Failed to resolve proxy host {}: {}", host, e),
+            }
+        }
+        
+        (resolved, resolved_hosts)
+    }
+    
+    /// Merge fetched entries into the pool, returning the newly added proxies.
+    fn merge_proxies(&self, entries: HashMap<ProxyUrl, FetchedProxy>) -> Vec<Arc<Proxy>> {
+        let now = Instant::now();
+        let mut proxies = self.proxies.write();
+        let mut added = Vec::new();
+        
+        for (url, fetched) in entries {
+            if let Some(proxy) = proxies.get_mut(&url) {
+                // Copies handed out keep sharing the statistics of the updated proxy
+                let proxy = Arc::make_mut(proxy);
+                proxy.sources = fetched.sources.clone();
+                proxy.weight = fetched.weight;
+                self.update_proxy(proxy, fetched, now);
+            } else {
+                let proxy = Arc::new(self.new_proxy(fetched));
+                added.push(Arc::clone(&proxy));
+                proxies.insert(url, proxy);
+            }
+        }
+        
+        added
+    }
+    
+    /// Add the source to the listed proxies, refreshing known ones and adding new ones.
+    ///
+    /// Returns the newly added proxies.
+    fn upsert_proxies(&self, proxies: &mut ProxyMap, name: &str, fetched: HashMap<ProxyUrl, FetchedProxy>, now: Instant) -> Vec<Arc<Proxy>> {
+        let mut added = Vec::new();
+        
+        for (url, fetched) in fetched {
+            if let Some(proxy) = proxies.get_mut(&url) {
+                let proxy = Arc::make_mut(proxy);
+                if !proxy.sources.iter().any(|s| s == name) {
+                    proxy.sources.push(name.to_string());
+                }
+                proxy.weight = proxy.weight.max(fetched.weight);
+                self.update_proxy(proxy, fetched, now);
+            } else {
+                let proxy = Arc::new(self.new_proxy(fetched));
+                added.push(Arc::clone(&proxy));
+                proxies.insert(url, proxy);
+            }
+        }
+        
+        added
+    }
+    
+    /// Create a pool member from a fetched entry.
+    fn new_proxy(&self, fetched: FetchedProxy) -> Proxy {
+        let mut proxy = Proxy::from_entry(fetched.entry, self.config.max_requests_per_second);
+        proxy.sources = fetched.sources;
+        proxy.weight = fetched.weight;
+        proxy.resolved_from = fetched.resolved_from;
+        proxy
+    }
+    
+    /// Refresh a known proxy with what its sources tell us, keeping its statistics.
+    fn update_proxy(&self, proxy: &mut Proxy, fetched: FetchedProxy, now: Instant) {
+        proxy.set_max_requests_per_second(
+            fetched.entry.max_requests_per_second.unwrap_or(self.config.max_requests_per_second));
+        proxy.credentials = fetched.entry.credentials;
+        proxy.metadata = fetched.entry.metadata;
+        proxy.resolved_from = fetched.resolved_from;
+        proxy.last_seen = now;
+    }
+    
+    /// Remove proxies that no source has listed within the retirement grace period.
+    fn retire_absent_proxies(&self) -> usize {
+        let grace = self.config.source_retirement_grace;
+        let mut proxies = self.proxies.write();
+        let before = proxies.len();
+        
+        proxies.retain(|_, p| {
+            let keep = p.last_seen.elapsed() <= grace;
+            if !keep {
+                info!("Retiring proxy {}: not listed by any source for {:?}", p.url, p.last_seen.elapsed());
+            }
+            keep
+        });
+        
+        before - proxies.len()
     }
     
     /// Check the health of all proxies in the pool.
     pub async fn check_all_proxies(&self) {
         info!("Starting health check for all proxies");
         
-        let proxies = {
-            let guard = self.proxies.read();
-            guard.clone()
-        };
+        // Forget expired bans
+        let now = Instant::now();
+        self.bans.write().retain(|_, until| ban_active(*until, now));
+        
+        let proxies = self.proxies.read().values().cloned().collect();
         
+        self.check_proxies(proxies).await;
+    }
+    
+    /// Check the health of the given proxies and update their status in the pool.
+    async fn check_proxies(&self, proxies: Vec<Arc<Proxy>>) {
         let mut futures = Vec::new();
         
-        for proxy in &proxies {
-            let proxy_url = proxy.url.clone();
+        for proxy in proxies {
             let check_url = self.config.health_check_url.clone();
             let timeout = self.config.health_check_timeout;
+            let builder = self.client_builder(&proxy);
             
             let future = async move {
                 let start = Instant::now();
                 
                 // Create a client using this proxy
-                let proxy_client = match reqwest::Client::builder()
+                let proxy_client = match builder
                     .timeout(timeout)
                     .proxy(proxy.to_reqwest_proxy().unwrap())
                     .build()
                 {
                     Ok(client) => client,
-                    Err(_) => return (proxy_url, false, None),
+                    Err(_) => return (proxy, false, None),
                 };
                 
                 // Test the proxy
                 match proxy_client.get(&check_url).send().await {
                     Ok(resp) if resp.status().is_success() => {
                         let elapsed = start.elapsed().as_secs_f64();
-                        (proxy_url, true, Some(elapsed))
+                        (proxy, true, Some(elapsed))
                     }
-                    _ => (proxy_url, false, None),
+                    _ => (proxy, false, None),
                 }
             };
             
@@ -140,46 +1070,124 @@ impl ProxyPool {
         let mut healthy_count = 0;
         let mut unhealthy_count = 0;
         
-        // Update proxy statuses based on health check results
-        {
-            let mut proxies = self.proxies.write();
+        // Update proxy statuses based on health check results, the statistics
+        // are shared with the pool member so no lock is needed
+        for (proxy, is_healthy, response_time) in results {
+            let old_status = proxy.record_check(is_healthy, response_time);
             
-            for (url, is_healthy, response_time) in results {
-                if let Some(proxy) = proxies.iter_mut().find(|p| p.url == url) {
-                    let old_status = proxy.status;
-                    
-                    if is_healthy {
-                        proxy.status = ProxyStatus::Healthy;
-                        proxy.response_time = response_time;
-                        healthy_count += 1;
-                    } else {
-                        proxy.status = ProxyStatus::Unhealthy;
-                        unhealthy_count += 1;
-                    }
-                    
-                    // Log status changes
-                    if old_status != proxy.status {
-                        info!("Proxy {} status changed: {:?} -> {:?}", 
-                            proxy.url, old_status, proxy.status);
-                    }
-                    
-                    proxy.last_check = Instant::now();
-                }
+            if is_healthy {
+                healthy_count += 1;
+            } else {
+                unhealthy_count += 1;
+            }
+            
+            // Log status changes
+            if old_status != proxy.status() {
+                info!("Proxy {} status changed: {:?} -> {:?}", 
+                    proxy.url, old_status, proxy.status());
             }
         }
         
+        self.rebuild_snapshot();
+        
         info!("Health check completed: {} healthy, {} unhealthy", 
             healthy_count, unhealthy_count);
     }
     
-    /// Get a proxy from the pool according to the configured selection strategy.
-    pub fn get_proxy(&self) -> Result<Proxy, NoProxyAvailable> {
+    /// Start building a client for requests through the given proxy.
+    ///
+    /// Unless `allow_unsafe_addresses` is set, a proxy hostname is checked again each
+    /// time it is resolved to connect, so a changed DNS answer cannot reach internal services.
+    pub(crate) fn client_builder(&self, proxy: &Proxy) -> reqwest::ClientBuilder {
+        let builder = reqwest::Client::builder();
+        if self.config.allow_unsafe_addresses || proxy.url.ip().is_some() {
+            return builder;
+        }
+        builder.dns_resolver(Arc::new(SafeResolver::new(proxy.url.host())))
+    }
+    
+    /// Recompute the selectable proxies from the pool members and the bans.
+    fn rebuild_snapshot(&self) {
+        let _guard = self.snapshot_lock.lock();
+        let now = Instant::now();
+        let bans = self.bans.read();
         let proxies = self.proxies.read();
         
-        // Filter healthy proxies
-        let healthy_proxies: Vec<&Proxy> = proxies.iter()
-            .filter(|p| p.status == ProxyStatus::Healthy)
+        // Healthy proxies only, zero-weight and banned proxies are never selected
+        let mut expires_at: Option<Instant> = None;
+        let selectable: Vec<Arc<Proxy>> = proxies.values()
+            .filter(|p| p.status() == ProxyStatus::Healthy && p.weight > 0.0)
+            .filter(|p| match bans.get(&p.url) {
+                Some(&Some(until)) if until > now => {
+                    expires_at = Some(expires_at.map_or(until, |at| at.min(until)));
+                    false
+                }
+                Some(None) => false,
+                _ => true,
+            })
+            .cloned()
             .collect();
+        
+        let cumulative_weights = selectable.iter()
+            .scan(0.0, |total, p| {
+                *total += p.weight;
+                Some(*total)
+            })
+            .collect();
+        let fastest = selectable.iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| {
+                a.response_time().unwrap_or(f64::MAX)
+                .partial_cmp(&b.response_time().unwrap_or(f64::MAX))
+                .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .map_or(0, |(i, _)| i);
+        
+        let available = selectable.len();
+        self.snapshot.store(Arc::new(Snapshot {
+            proxies: selectable,
+            cumulative_weights,
+            fastest,
+            expires_at,
+        }));
+        
+        self.check_minimum(available);
+    }
+    
+    /// Trigger a replenishment and emit events when the pool crosses `min_available_proxies`.
+    fn check_minimum(&self, available: usize) {
+        if !self.enforce_minimum.load(Ordering::Relaxed) {
+            return;
+        }
+        
+        let required = self.config.min_available_proxies;
+        let below = available < required;
+        let was_below = self.below_minimum.swap(below, Ordering::Relaxed);
+        
+        if below {
+            if !was_below {
+                warn!("Only {} of the required {} proxies are available, replenishing", available, required);
+                let _ = self.events.send(PoolEvent::BelowMinimum { available, required });
+            }
+            self.replenish.notify_one();
+        } else if was_below {
+            info!("{} proxies are available again", available);
+            let _ = self.events.send(PoolEvent::MinimumRestored { available, required });
+        }
+    }
+    
+    /// Get a proxy from the pool according to the configured selection strategy.
+    ///
+    /// Selection works on a precomputed snapshot of the healthy proxies and takes no
+    /// lock. The returned proxy shares its statistics with the pool member.
+    pub fn get_proxy(&self) -> Result<Arc<Proxy>, NoProxyAvailable> {
+        let mut snapshot = self.snapshot.load();
+        if snapshot.expires_at.is_some_and(|at| at <= Instant::now()) {
+            // A ban ended, the banned proxy is selectable again
+            self.rebuild_snapshot();
+            snapshot = self.snapshot.load();
+        }
+        let healthy_proxies = &snapshot.proxies;
             
         if healthy_proxies.is_empty() {
             return Err(NoProxyAvailable);
@@ -188,14 +1196,8 @@ impl ProxyPool {
         // Select a proxy based on the configured strategy
         let selected = match self.config.selection_strategy {
             ProxySelectionStrategy::FastestResponse => {
-                // Select the proxy with the fastest response time
-                healthy_proxies.iter()
-                    .min_by(|a, b| {
-                        a.response_time.unwrap_or(f64::MAX)
-                        .partial_cmp(&b.response_time.unwrap_or(f64::MAX))
-                        .unwrap_or(std::cmp::Ordering::Equal)
-                    })
-                    .unwrap()
+                // Fastest proxy as of the latest health check
+                &healthy_proxies[snapshot.fastest]
             },
             ProxySelectionStrategy::MostReliable => {
                 // Select the proxy with the highest success rate
@@ -207,48 +1209,55 @@ impl ProxyPool {
                     .unwrap()
             },
             ProxySelectionStrategy::Random => {
-                // Select a random healthy proxy
-                let mut rng = rand::rng();
-                let idx = rng.random_range(0..healthy_proxies.len());
-                &healthy_proxies[idx]
+                // Select a random healthy proxy, biased by weight
+                let total_weight = snapshot.cumulative_weights[healthy_proxies.len() - 1];
+                if total_weight.is_finite() && total_weight > 0.0 {
+                    let point = rand::rng().random_range(0.0..total_weight);
+                    let index = snapshot.cumulative_weights.partition_point(|&w| w <= point);
+                    &healthy_proxies[index.min(healthy_proxies.len() - 1)]
+                } else {
+                    // Weights too large to sum, fall back to uniform selection
+                    &healthy_proxies[rand::rng().random_range(0..healthy_proxies.len())]
+                }
             },
             ProxySelectionStrategy::RoundRobin => {
                 // Round-robin selection
-                let mut last_index = self.last_proxy_index.lock();
-                *last_index = (*last_index + 1) % healthy_proxies.len();
-                &healthy_proxies[*last_index]
+                let index = self.last_proxy_index.fetch_add(1, Ordering::Relaxed).wrapping_add(1);
+                &healthy_proxies[index % healthy_proxies.len()]
             }
         };
             
-        Ok((*selected).clone())
+        Ok(Arc::clone(selected))
     }
     
     /// Report a successful request through a proxy.
-    pub fn report_proxy_success(&self, url: &str) {
-        let mut proxies = self.proxies.write();
-        if let Some(proxy) = proxies.iter_mut().find(|p| p.url == url) {
-            proxy.success_count += 1;
-            proxy.status = ProxyStatus::Healthy;
+    pub fn report_proxy_success(&self, url: &ProxyUrl) {
+        let proxy = self.proxies.read().get(url).cloned();
+        if let Some(proxy) = proxy {
+            proxy.record_success();
+            if proxy.set_status(ProxyStatus::Healthy) != ProxyStatus::Healthy {
+                self.rebuild_snapshot();
+            }
         }
     }
     
     /// Report a failed request through a proxy.
-    pub fn report_proxy_failure(&self, url: &str) {
-        let mut proxies = self.proxies.write();
-        if let Some(proxy) = proxies.iter_mut().find(|p| p.url == url) {
-            proxy.failure_count += 1;
+    pub fn report_proxy_failure(&self, url: &ProxyUrl) {
+        let proxy = self.proxies.read().get(url).cloned();
+        if let Some(proxy) = proxy {
+            let failures = proxy.record_failure();
+            let successes = proxy.success_count();
             
             // Mark as unhealthy if failure ratio is too high
-            let failure_ratio = proxy.failure_count as f64 / 
-                (proxy.success_count + proxy.failure_count) as f64;
+            let failure_ratio = failures as f64 / (successes + failures) as f64;
                 
-            if failure_ratio > 0.5 && proxy.failure_count >= 3 {
-                let old_status = proxy.status;
-                proxy.status = ProxyStatus::Unhealthy;
+            if failure_ratio > 0.5 && failures >= 3 {
+                let old_status = proxy.set_status(ProxyStatus::Unhealthy);
                 
                 if old_status != ProxyStatus::Unhealthy {
                     warn!("Proxy {} marked unhealthy: {} failures, {} successes", 
-                        proxy.url, proxy.failure_count, proxy.success_count);
+                        proxy.url, failures, successes);
+                    self.rebuild_snapshot();
                 }
             }
         }
@@ -258,10 +1267,170 @@ impl ProxyPool {
     pub fn get_stats(&self) -> (usize, usize) {
         let proxies = self.proxies.read();
         let total = proxies.len();
-        let healthy = proxies.iter()
-            .filter(|p| p.status == ProxyStatus::Healthy)
+        let healthy = proxies.values()
+            .filter(|p| p.status() == ProxyStatus::Healthy)
             .count();
             
         (total, healthy)
     }
 }
+
+impl Drop for ProxyPool {
+    fn drop(&mut self) {
+        // Background tasks only hold weak references, stop them right away
+        self.cancel.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::{FileSource, StaticSource};
+    use std::path::PathBuf;
+
+    /// A pool whose health checks fail fast and never run in the background.
+    async fn test_pool(source: impl ProxySource + 'static, grace: Duration) -> Arc<ProxyPool> {
+        let config = ProxyPoolConfig::builder()
+            .source(source)
+            .allow_unsafe_addresses(true)
+            .health_check_url("http://127.0.0.1:9/")
+            .health_check_timeout(Duration::from_secs(1))
+            .health_check_interval(Duration::from_secs(3600))
+            .min_available_proxies(0)
+            .source_retirement_grace(grace)
+            .build();
+        ProxyPool::new(config).await.unwrap()
+    }
+
+    /// A proxy list file in the temp directory, unique to the test.
+    fn list_file(name: &str, content: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("reqwest-proxy-pool-{}-{}.txt", std::process::id(), name));
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn url(input: &str) -> ProxyUrl {
+        ProxyUrl::parse_with_default(input, ProxyScheme::Http).unwrap()
+    }
+
+    fn member(pool: &ProxyPool, input: &str) -> Option<Arc<Proxy>> {
+        pool.proxies.read().get(&url(input)).cloned()
+    }
+
+    fn members(pool: &ProxyPool) -> Vec<String> {
+        let mut urls: Vec<String> = pool.proxies.read().keys().map(ToString::to_string).collect();
+        urls.sort();
+        urls
+    }
+
+    #[tokio::test]
+    async fn unresolvable_hostnames_are_unsafe() {
+        let urls = [
+            ProxyUrl::new(ProxyScheme::Http, "proxy.invalid", 8080).unwrap(),
+            ProxyUrl::new(ProxyScheme::Http, "localhost", 8080).unwrap(),
+            ProxyUrl::new(ProxyScheme::Http, "203.0.113.10", 8080).unwrap(),
+        ];
+        let unsafe_hosts = ProxyPool::unsafe_hostnames(urls.iter()).await;
+        assert_eq!(unsafe_hosts, HashSet::from(["proxy.invalid".to_string(), "localhost".to_string()]));
+    }
+
+    #[tokio::test]
+    async fn refresh_keeps_statistics_and_retires_absent_proxies() {
+        let path = list_file("refresh", "http://127.0.0.1:1\nhttp://127.0.0.1:2\n");
+        let pool = test_pool(FileSource::new(&path), Duration::from_millis(300)).await;
+        assert_eq!(members(&pool), ["http://127.0.0.1:1", "http://127.0.0.1:2"]);
+
+        member(&pool, "http://127.0.0.1:1").unwrap().record_success();
+        member(&pool, "http://127.0.0.1:1").unwrap().record_success();
+        std::fs::write(&path, "http://127.0.0.1:1\nhttp://127.0.0.1:3\n").unwrap();
+        pool.refresh_sources().await;
+
+        // The absent proxy is kept until the grace period ends
+        assert_eq!(members(&pool), ["http://127.0.0.1:1", "http://127.0.0.1:2", "http://127.0.0.1:3"]);
+        assert_eq!(member(&pool, "http://127.0.0.1:1").unwrap().success_count(), 2);
+
+        time::sleep(Duration::from_millis(400)).await;
+        pool.refresh_sources().await;
+        assert_eq!(members(&pool), ["http://127.0.0.1:1", "http://127.0.0.1:3"]);
+        assert_eq!(member(&pool, "http://127.0.0.1:1").unwrap().success_count(), 2);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn reload_applies_file_changes() {
+        let path = list_file("reload", "http://127.0.0.1:1\nhttp://127.0.0.1:2\n");
+        let pool = test_pool(FileSource::new(&path).watch(Duration::from_secs(3600)), Duration::from_secs(3600)).await;
+        let source = Arc::clone(&pool.config.sources[0]);
+        member(&pool, "http://127.0.0.1:1").unwrap().record_success();
+
+        std::fs::write(&path, "http://127.0.0.1:1\nhttp://127.0.0.1:30\n").unwrap();
+        assert!(source.has_changed().await);
+        pool.reload_source(&source).await;
+
+        // Removed lines are dropped right away, unchanged proxies keep their statistics
+        assert_eq!(members(&pool), ["http://127.0.0.1:1", "http://127.0.0.1:30"]);
+        assert_eq!(member(&pool, "http://127.0.0.1:1").unwrap().success_count(), 1);
+
+        // An unreadable file leaves the pool as is
+        std::fs::remove_file(&path).unwrap();
+        pool.reload_source(&source).await;
+        assert_eq!(members(&pool), ["http://127.0.0.1:1", "http://127.0.0.1:30"]);
+    }
+
+    #[tokio::test]
+    async fn banned_proxies_are_not_selected() {
+        let entries = vec![ProxyEntry::new(url("http://127.0.0.1:1")), ProxyEntry::new(url("http://127.0.0.1:2"))];
+        let pool = test_pool(StaticSource::new(entries), Duration::from_secs(3600)).await;
+        for proxy in pool.proxies.read().values() {
+            proxy.set_status(ProxyStatus::Healthy);
+        }
+        pool.rebuild_snapshot();
+
+        let first = url("http://127.0.0.1:1");
+        let second = url("http://127.0.0.1:2");
+        pool.ban_proxy(&first, Duration::from_millis(200));
+        assert!(pool.is_banned(&first));
+        for _ in 0..10 {
+            assert_eq!(pool.get_proxy().unwrap().url, second);
+        }
+
+        // A ban too long to represent lasts until it is lifted
+        pool.ban_proxy(&second, Duration::MAX);
+        assert!(pool.is_banned(&second));
+        assert!(pool.get_proxy().is_err());
+
+        // An expired ban needs no unban
+        time::sleep(Duration::from_millis(300)).await;
+        assert!(!pool.is_banned(&first));
+        assert_eq!(pool.get_proxy().unwrap().url, first);
+        assert!(!pool.unban_proxy(&first));
+
+        assert!(pool.unban_proxy(&second));
+        assert!(!pool.is_banned(&second));
+        assert_eq!(pool.get_stats(), (2, 2));
+        pool.ban_proxy(&first, Duration::MAX);
+        assert_eq!(pool.get_proxy().unwrap().url, second);
+    }
+
+    #[tokio::test]
+    async fn remove_proxy_drops_resolved_addresses() {
+        let config = ProxyPoolConfig::builder()
+            .allow_unsafe_addresses(true)
+            .resolve_hostnames(true)
+            .health_check_url("http://127.0.0.1:9/")
+            .health_check_timeout(Duration::from_secs(1))
+            .health_check_interval(Duration::from_secs(3600))
+            .min_available_proxies(0)
+            .build();
+        let pool = ProxyPool::new(config).await.unwrap();
+
+        assert!(pool.add_proxy(ProxyEntry::new(url("http://localhost:1"))).await);
+        assert!(!members(&pool).is_empty());
+        assert!(pool.proxies.read().values().all(|p| p.resolved_from.as_deref() == Some("localhost")));
+
+        assert!(pool.remove_proxy(&url("http://localhost:1")));
+        assert!(members(&pool).is_empty());
+        assert!(!pool.remove_proxy(&url("http://localhost:1")));
+    }
+}
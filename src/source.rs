@@ -0,0 +1,1040 @@
+//! Sources that provide proxy entries to the pool.
+
+use crate::error::SourceError;
+use crate::proxy::{ProxyEntry, ProxyScheme};
+use crate::utils;
+
+use async_trait::async_trait;
+use log::debug;
+use parking_lot::Mutex;
+use reqwest::header::{HeaderValue, ACCEPT_ENCODING, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::{Client, StatusCode};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::io::AsyncReadExt;
+use tokio::sync::OnceCell;
+
+/// A source of proxy entries, such as a remote list, a file or an inventory service.
+///
+/// Sources are fetched when the pool is created. Implement this trait to feed the
+/// pool from your own systems.
+#[async_trait]
+pub trait ProxySource: Send + Sync {
+    /// Name of the source, used in logs.
+    fn name(&self) -> String;
+
+    /// Fetch the current list of proxies from this source.
+    async fn fetch(&self) -> Result<Vec<ProxyEntry>, SourceError>;
+
+    /// Fetch the current list together with the rows skipped while parsing it.
+    ///
+    /// The pool fetches sources through this method, the default wraps `fetch`.
+    /// Sources that parse tabular lists can override it to report rejected rows
+    /// in `SourceOutcome::rejected_rows`.
+    async fn fetch_list(&self) -> Result<FetchedList, SourceError> {
+        Ok(FetchedList::from(self.fetch().await?))
+    }
+
+    /// Selection weight of proxies from this source, relative to other sources.
+    ///
+    /// Weights bias the `Random` strategy, a weight of zero keeps the
+    /// source's proxies in the pool but never selects them.
+    /// Negative and non-finite weights are ignored and count as 1.0.
+    fn weight(&self) -> f64 {
+        1.0
+    }
+
+    /// How often to poll `has_changed`, `None` if the source is not watched.
+    fn watch_interval(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Timeout and retry policy for fetching this source, `None` to use the pool's
+    /// `source_retry_policy`.
+    fn retry_policy(&self) -> Option<RetryPolicy> {
+        None
+    }
+
+    /// Whether the source changed since the last fetch.
+    ///
+    /// Polled every `watch_interval`, a change reloads just this source.
+    async fn has_changed(&self) -> bool {
+        false
+    }
+}
+
+/// Entries fetched from a source and the rows that could not be parsed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FetchedList {
+    /// The parsed proxy entries.
+    pub entries: Vec<ProxyEntry>,
+    /// Rows skipped while parsing, per reason.
+    pub rejected_rows: BTreeMap<RowRejection, usize>,
+}
+
+impl From<Vec<ProxyEntry>> for FetchedList {
+    fn from(entries: Vec<ProxyEntry>) -> Self {
+        Self {
+            entries,
+            rejected_rows: BTreeMap::new(),
+        }
+    }
+}
+
+/// Result of the most recent fetch of a source.
+#[derive(Debug, Clone)]
+pub struct SourceOutcome {
+    /// Name of the source.
+    pub source: String,
+    /// Number of proxies the source yielded.
+    pub fetched: usize,
+    /// Why the fetch failed, `None` on success.
+    pub error: Option<Arc<SourceError>>,
+    /// Time when the fetch completed.
+    pub fetched_at: Instant,
+    /// How long the fetch took, including retries.
+    pub duration: Duration,
+    /// Number of fetch attempts made.
+    pub attempts: usize,
+    /// Rows skipped while parsing the source, per reason, see `ProxySource::fetch_list`.
+    pub rejected_rows: BTreeMap<RowRejection, usize>,
+}
+
+impl SourceOutcome {
+    /// Whether the source was fetched successfully.
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Summary of the most recent fetch of all sources.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IngestionSummary {
+    /// Number of sources fetched.
+    pub sources: usize,
+    /// Number of sources that failed.
+    pub failed: usize,
+    /// Total number of proxies the sources yielded.
+    pub fetched: usize,
+    /// Number of distinct proxies after deduplication.
+    pub unique: usize,
+    /// Wall-clock time of the fetch, sources are fetched concurrently.
+    pub duration: Duration,
+}
+
+/// Timeout and retry policy for fetching a source.
+///
+/// Each attempt is bounded by `timeout`. Transient failures are retried up to
+/// `max_retries` times, waiting `backoff` before the first retry and doubling
+/// the wait after each one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Timeout of a single fetch attempt.
+    pub timeout: Duration,
+    /// Number of retries after the first attempt.
+    pub max_retries: usize,
+    /// Wait before the first retry.
+    pub backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Create a policy with the given timeout and no retries.
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            max_retries: 0,
+            backoff: Duration::from_secs(1),
+        }
+    }
+
+    /// Set the number of retries after the first attempt.
+    pub fn max_retries(mut self, retries: usize) -> Self {
+        self.max_retries = retries;
+        self
+    }
+
+    /// Set the wait before the first retry.
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// The wait before the given retry, starting at 1.
+    pub(crate) fn delay(&self, retry: usize) -> Duration {
+        self.backoff.saturating_mul(1u32.checked_shl(retry.saturating_sub(1) as u32).unwrap_or(u32::MAX))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(30)).max_retries(2)
+    }
+}
+
+/// Statistics about the proxies a source contributes to the pool.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceStats {
+    /// Name of the source.
+    pub source: String,
+    /// Number of proxies the source yielded on its latest fetch.
+    pub fetched: usize,
+    /// Number of distinct pool members listed by the source.
+    pub unique: usize,
+    /// Number of those members that are currently healthy.
+    pub healthy: usize,
+    /// Success rate of requests through those members.
+    pub success_rate: f64,
+}
+
+impl fmt::Debug for dyn ProxySource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ProxySource").field(&self.name()).finish()
+    }
+}
+
+/// Build a source from a location string.
+///
+/// - `http://...` and `https://...` fetch a remote list
+/// - `env:VAR_NAME` reads a list from an environment variable
+/// - `system-env` reads `HTTP_PROXY`, `HTTPS_PROXY` and `ALL_PROXY`
+/// - `stdin` reads a list piped into the process
+/// - `proxychains:LOCATION` and `clash:LOCATION` read a `proxychains.conf` or Clash
+///   YAML config from a URL or file
+/// - anything else is read as a file path
+impl From<&str> for Box<dyn ProxySource> {
+    fn from(location: &str) -> Self {
+        let config_format = [("proxychains:", SourceFormat::Proxychains), ("clash:", SourceFormat::Clash)]
+            .into_iter()
+            .find_map(|(prefix, format)| location.strip_prefix(prefix).map(|rest| (rest, format)));
+        if let Some((location, format)) = config_format {
+            return if is_http_url(location) {
+                Box::new(UrlSource::new(location).format(format))
+            } else {
+                Box::new(FileSource::new(location).format(format))
+            };
+        }
+
+        if is_http_url(location) {
+            Box::new(UrlSource::new(location))
+        } else if let Some(var) = location.strip_prefix("env:") {
+            Box::new(EnvSource::new(var))
+        } else if location == "system-env" {
+            Box::new(SystemProxySource::new())
+        } else if location == "stdin" {
+            Box::new(StdinSource::new())
+        } else {
+            Box::new(FileSource::new(location))
+        }
+    }
+}
+
+/// Whether a location is an `http://` or `https://` URL, in any case.
+///
+/// Paths such as `http_proxies.txt` or `httpd/list.txt` are files.
+fn is_http_url(location: &str) -> bool {
+    ["http://", "https://"]
+        .iter()
+        .any(|prefix| location.get(..prefix.len()).is_some_and(|start| start.eq_ignore_ascii_case(prefix)))
+}
+
+impl From<String> for Box<dyn ProxySource> {
+    fn from(location: String) -> Self {
+        location.as_str().into()
+    }
+}
+
+/// Mapping from JSON object fields to proxy fields.
+///
+/// Field names starting with `/` are treated as JSON pointers (e.g. `"/geo/country"`),
+/// so nested provider objects can be mapped as well.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonFieldMapping {
+    /// JSON pointer to the array of proxy objects, `None` if the document itself is the array.
+    pub root: Option<String>,
+    /// Field holding a complete proxy address (e.g. `"socks5://1.2.3.4:1080"`), takes precedence over `host`/`port`.
+    pub address: Option<String>,
+    /// Field holding the proxy host.
+    pub host: String,
+    /// Field holding the proxy port, as number or string.
+    pub port: String,
+    /// Field holding the proxy scheme, as string or array of strings.
+    pub scheme: Option<String>,
+    /// Field holding the proxy username.
+    pub username: Option<String>,
+    /// Field holding the proxy password.
+    pub password: Option<String>,
+    /// Field holding the proxy country.
+    pub country: Option<String>,
+    /// Field holding the proxy anonymity level.
+    pub anonymity: Option<String>,
+    /// Field holding the proxy provider.
+    pub provider: Option<String>,
+    /// Additional fields copied into `ProxyMetadata::extra`.
+    pub extra: Vec<String>,
+}
+
+impl Default for JsonFieldMapping {
+    fn default() -> Self {
+        Self {
+            root: None,
+            address: None,
+            host: "ip".to_string(),
+            port: "port".to_string(),
+            scheme: Some("protocol".to_string()),
+            username: Some("username".to_string()),
+            password: Some("password".to_string()),
+            country: Some("country".to_string()),
+            anonymity: Some("anonymity".to_string()),
+            provider: Some("provider".to_string()),
+            extra: Vec::new(),
+        }
+    }
+}
+
+/// Options for CSV/TSV proxy lists, mapping columns to proxy fields.
+///
+/// Columns are looked up by header name (case-insensitive) or, when the
+/// name is a number, by zero-based column index.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvOptions {
+    /// Field delimiter, `b','` for CSV and `b'\t'` for TSV.
+    pub delimiter: u8,
+    /// Whether the first row holds column names.
+    pub has_headers: bool,
+    /// Column holding the proxy host.
+    pub host: String,
+    /// Column holding the proxy port.
+    pub port: String,
+    /// Column holding the proxy scheme.
+    pub scheme: Option<String>,
+    /// Column holding the proxy username.
+    pub username: Option<String>,
+    /// Column holding the proxy password.
+    pub password: Option<String>,
+    /// Column holding the proxy country.
+    pub country: Option<String>,
+    /// Column holding the proxy anonymity level.
+    pub anonymity: Option<String>,
+    /// Column holding the proxy provider.
+    pub provider: Option<String>,
+    /// Additional columns copied into `ProxyMetadata::extra`.
+    pub extra: Vec<String>,
+}
+
+impl CsvOptions {
+    /// Options for comma-separated lists with a header row.
+    pub fn csv() -> Self {
+        Self::default()
+    }
+
+    /// Options for tab-separated lists with a header row.
+    pub fn tsv() -> Self {
+        Self {
+            delimiter: b'\t',
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            has_headers: true,
+            host: "host".to_string(),
+            port: "port".to_string(),
+            scheme: Some("protocol".to_string()),
+            username: Some("username".to_string()),
+            password: Some("password".to_string()),
+            country: Some("country".to_string()),
+            anonymity: Some("anonymity".to_string()),
+            provider: Some("provider".to_string()),
+            extra: Vec::new(),
+        }
+    }
+}
+
+/// Why a CSV row could not be turned into a proxy entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RowRejection {
+    /// The row could not be read, e.g. wrong number of fields.
+    Malformed,
+    /// The host column is empty.
+    MissingHost,
+    /// The port column is empty.
+    MissingPort,
+    /// The port column is not a valid port number.
+    InvalidPort,
+    /// The scheme column holds an unsupported scheme.
+    UnsupportedScheme,
+    /// Host and port do not form a valid proxy address.
+    InvalidAddress,
+}
+
+impl fmt::Display for RowRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            RowRejection::Malformed => "malformed",
+            RowRejection::MissingHost => "missing host",
+            RowRejection::MissingPort => "missing port",
+            RowRejection::InvalidPort => "invalid port",
+            RowRejection::UnsupportedScheme => "unsupported scheme",
+            RowRejection::InvalidAddress => "invalid address",
+        })
+    }
+}
+
+/// Format of a proxy list source.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum SourceFormat {
+    /// One proxy per line.
+    #[default]
+    Text,
+    /// A JSON array of proxy objects.
+    Json(Box<JsonFieldMapping>),
+    /// A CSV or TSV table of proxies.
+    Csv(Box<CsvOptions>),
+    /// The `[ProxyList]` of a `proxychains.conf`, e.g. `socks5 1.2.3.4 1080 user pass`.
+    Proxychains,
+    /// The `proxies:` of a Clash YAML config, only `http` and `socks5` proxies are used.
+    Clash,
+}
+
+impl SourceFormat {
+    /// JSON format with the given field mapping.
+    pub fn json(mapping: JsonFieldMapping) -> Self {
+        SourceFormat::Json(Box::new(mapping))
+    }
+
+    /// CSV/TSV format with the given column options.
+    pub fn csv(options: CsvOptions) -> Self {
+        SourceFormat::Csv(Box::new(options))
+    }
+}
+
+/// Authentication sent when fetching a proxy list.
+#[derive(Clone)]
+pub enum FetchAuth {
+    /// `Authorization: Bearer <token>`.
+    Bearer(String),
+    /// HTTP basic authentication.
+    Basic {
+        /// Username to send.
+        username: String,
+        /// Password to send, if any.
+        password: Option<String>,
+    },
+}
+
+impl fmt::Debug for FetchAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never leak secrets into logs
+        match self {
+            FetchAuth::Bearer(_) => f.write_str("Bearer(***)"),
+            FetchAuth::Basic { username, .. } => f
+                .debug_struct("Basic")
+                .field("username", username)
+                .field("password", &"***")
+                .finish(),
+        }
+    }
+}
+
+/// Options for the HTTP request fetching a proxy list.
+#[derive(Debug, Clone)]
+pub struct FetchOptions {
+    /// Timeout for the whole request.
+    pub timeout: Duration,
+    /// Extra request headers.
+    pub headers: Vec<(String, String)>,
+    /// Authentication for private provider APIs.
+    pub auth: Option<FetchAuth>,
+    /// Status the response must have, any 2xx status if `None`.
+    pub expected_status: Option<StatusCode>,
+    /// Proxy URL to fetch the list through.
+    pub proxy: Option<String>,
+    /// Send `If-None-Match`/`If-Modified-Since` and keep the previous list on `304 Not Modified`.
+    pub conditional: bool,
+}
+
+impl FetchOptions {
+    /// Create default options, a 30 second timeout and no extras.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the timeout for the whole request.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Add a request header.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Authenticate with a bearer token.
+    pub fn bearer_auth(mut self, token: impl Into<String>) -> Self {
+        self.auth = Some(FetchAuth::Bearer(token.into()));
+        self
+    }
+
+    /// Authenticate with HTTP basic authentication.
+    pub fn basic_auth(mut self, username: impl Into<String>, password: Option<impl Into<String>>) -> Self {
+        self.auth = Some(FetchAuth::Basic {
+            username: username.into(),
+            password: password.map(Into::into),
+        });
+        self
+    }
+
+    /// Require the response to have exactly this status.
+    pub fn expected_status(mut self, status: StatusCode) -> Self {
+        self.expected_status = Some(status);
+        self
+    }
+
+    /// Fetch the list through the given proxy.
+    pub fn proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.proxy = Some(proxy.into());
+        self
+    }
+
+    /// Enable or disable conditional requests.
+    pub fn conditional(mut self, conditional: bool) -> Self {
+        self.conditional = conditional;
+        self
+    }
+}
+
+impl Default for FetchOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            headers: Vec::new(),
+            auth: None,
+            expected_status: None,
+            proxy: None,
+            conditional: true,
+        }
+    }
+}
+
+/// Validators and entries of the last successful fetch, for conditional requests.
+#[derive(Debug, Default)]
+struct CachedList {
+    etag: Option<HeaderValue>,
+    last_modified: Option<HeaderValue>,
+    list: Option<FetchedList>,
+}
+
+/// A proxy list fetched over HTTP(S).
+///
+/// Responses compressed with gzip or zstd, either via `Content-Encoding` or as
+/// `.gz`/`.zst` files, are decompressed transparently.
+#[derive(Debug, Clone)]
+pub struct UrlSource {
+    url: String,
+    default_scheme: ProxyScheme,
+    format: SourceFormat,
+    weight: f64,
+    options: FetchOptions,
+    retry_policy: Option<RetryPolicy>,
+    cache: Arc<Mutex<CachedList>>,
+}
+
+impl UrlSource {
+    /// Create a plain-text source whose bare entries default to SOCKS5.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            default_scheme: ProxyScheme::default(),
+            format: SourceFormat::default(),
+            weight: 1.0,
+            options: FetchOptions::default(),
+            retry_policy: None,
+            cache: Arc::new(Mutex::new(CachedList::default())),
+        }
+    }
+
+    /// Set the options for the HTTP request.
+    pub fn fetch_options(mut self, options: FetchOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Set the timeout and retry policy, overriding the pool's `source_retry_policy`.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// Set the scheme assumed for entries without an explicit scheme.
+    pub fn default_scheme(mut self, scheme: ProxyScheme) -> Self {
+        self.default_scheme = scheme;
+        self
+    }
+
+    /// Set the format of the proxy list.
+    pub fn format(mut self, format: SourceFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Set the selection weight of proxies from this source.
+    pub fn weight(mut self, weight: f64) -> Self {
+        self.weight = weight;
+        self
+    }
+}
+
+#[async_trait]
+impl ProxySource for UrlSource {
+    fn name(&self) -> String {
+        self.url.clone()
+    }
+
+    async fn fetch(&self) -> Result<Vec<ProxyEntry>, SourceError> {
+        Ok(self.fetch_list().await?.entries)
+    }
+
+    async fn fetch_list(&self) -> Result<FetchedList, SourceError> {
+        let mut client = Client::builder().timeout(self.options.timeout);
+        if let Some(proxy) = &self.options.proxy {
+            client = client.proxy(reqwest::Proxy::all(proxy)?);
+        }
+
+        let mut request = client.build()?.get(&self.url);
+        // Compressed responses are decoded by their magic bytes, reqwest is built
+        // without automatic decompression so proxied responses stay untouched
+        if !self.options.headers.iter().any(|(name, _)| name.eq_ignore_ascii_case(ACCEPT_ENCODING.as_str())) {
+            request = request.header(ACCEPT_ENCODING, "gzip, zstd");
+        }
+        for (name, value) in &self.options.headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        request = match &self.options.auth {
+            Some(FetchAuth::Bearer(token)) => request.bearer_auth(token),
+            Some(FetchAuth::Basic { username, password }) => request.basic_auth(username, password.as_ref()),
+            None => request,
+        };
+        if self.options.conditional {
+            let cache = self.cache.lock();
+            if cache.list.is_some() {
+                if let Some(etag) = &cache.etag {
+                    request = request.header(IF_NONE_MATCH, etag.clone());
+                }
+                if let Some(last_modified) = &cache.last_modified {
+                    request = request.header(IF_MODIFIED_SINCE, last_modified.clone());
+                }
+            }
+        }
+
+        let mut response = request.send().await?;
+        let status = response.status();
+        if status == StatusCode::NOT_MODIFIED && self.options.conditional {
+            if let Some(list) = self.cache.lock().list.clone() {
+                debug!("Proxy list {} not modified, keeping {} proxies", self.url, list.entries.len());
+                return Ok(list);
+            }
+        }
+        let accepted = match self.options.expected_status {
+            Some(expected) => status == expected,
+            None => status.is_success(),
+        };
+        if !accepted {
+            return Err(SourceError::HttpStatus(status));
+        }
+        let etag = response.headers().get(ETAG).cloned();
+        let last_modified = response.headers().get(LAST_MODIFIED).cloned();
+        if response.content_length().is_some_and(|length| length > utils::MAX_LIST_BYTES) {
+            return Err(utils::list_too_large());
+        }
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            if (body.len() + chunk.len()) as u64 > utils::MAX_LIST_BYTES {
+                return Err(utils::list_too_large());
+            }
+            body.extend_from_slice(&chunk);
+        }
+        let content = utils::decode_content(&body)?;
+        let list = utils::parse_source_content(&content, &self.url, self.default_scheme, &self.format)?;
+
+        if self.options.conditional {
+            *self.cache.lock() = CachedList {
+                etag,
+                last_modified,
+                list: Some(list.clone()),
+            };
+        }
+        Ok(list)
+    }
+
+    fn weight(&self) -> f64 {
+        self.weight
+    }
+
+    fn retry_policy(&self) -> Option<RetryPolicy> {
+        self.retry_policy
+    }
+}
+
+/// A proxy list read from a local file.
+///
+/// Files compressed with gzip or zstd (e.g. `proxies.txt.gz`) are decompressed transparently.
+#[derive(Debug, Clone)]
+pub struct FileSource {
+    path: PathBuf,
+    default_scheme: ProxyScheme,
+    format: SourceFormat,
+    weight: f64,
+    watch_interval: Option<Duration>,
+    stamp: Arc<Mutex<Option<FileStamp>>>,
+}
+
+/// Modification time and size of a file, used to detect changes.
+type FileStamp = (SystemTime, u64);
+
+/// Read the stamp of a file, `None` if it cannot be read.
+async fn file_stamp(path: &PathBuf) -> Option<FileStamp> {
+    let metadata = tokio::fs::metadata(path).await.ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+impl FileSource {
+    /// Create a plain-text source whose bare entries default to SOCKS5.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            default_scheme: ProxyScheme::default(),
+            format: SourceFormat::default(),
+            weight: 1.0,
+            watch_interval: None,
+            stamp: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Poll the file for changes and hot-reload it.
+    ///
+    /// On change, added proxies join the pool, removed proxies are dropped and
+    /// unchanged proxies keep their statistics.
+    pub fn watch(mut self, interval: Duration) -> Self {
+        self.watch_interval = Some(interval);
+        self
+    }
+
+    /// Set the scheme assumed for entries without an explicit scheme.
+    pub fn default_scheme(mut self, scheme: ProxyScheme) -> Self {
+        self.default_scheme = scheme;
+        self
+    }
+
+    /// Set the format of the proxy list.
+    pub fn format(mut self, format: SourceFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Set the selection weight of proxies from this source.
+    pub fn weight(mut self, weight: f64) -> Self {
+        self.weight = weight;
+        self
+    }
+}
+
+#[async_trait]
+impl ProxySource for FileSource {
+    fn name(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    async fn fetch(&self) -> Result<Vec<ProxyEntry>, SourceError> {
+        Ok(self.fetch_list().await?.entries)
+    }
+
+    async fn fetch_list(&self) -> Result<FetchedList, SourceError> {
+        *self.stamp.lock() = file_stamp(&self.path).await;
+        let mut bytes = Vec::new();
+        let file = tokio::fs::File::open(&self.path).await.map_err(|error| SourceError::Io {
+            path: self.path.clone(),
+            error,
+        })?;
+        file.take(utils::MAX_LIST_BYTES + 1).read_to_end(&mut bytes).await.map_err(|error| SourceError::Io {
+            path: self.path.clone(),
+            error,
+        })?;
+        if bytes.len() as u64 > utils::MAX_LIST_BYTES {
+            return Err(utils::list_too_large());
+        }
+        let content = utils::decode_content(&bytes)?;
+        utils::parse_source_content(&content, &self.name(), self.default_scheme, &self.format)
+    }
+
+    fn weight(&self) -> f64 {
+        self.weight
+    }
+
+    fn watch_interval(&self) -> Option<Duration> {
+        self.watch_interval
+    }
+
+    async fn has_changed(&self) -> bool {
+        let stamp = file_stamp(&self.path).await;
+        *self.stamp.lock() != stamp
+    }
+}
+
+/// A proxy list read from an environment variable.
+#[derive(Debug, Clone)]
+pub struct EnvSource {
+    var: String,
+    default_scheme: ProxyScheme,
+    format: SourceFormat,
+    weight: f64,
+}
+
+impl EnvSource {
+    /// Create a plain-text source whose bare entries default to SOCKS5.
+    pub fn new(var: impl Into<String>) -> Self {
+        Self {
+            var: var.into(),
+            default_scheme: ProxyScheme::default(),
+            format: SourceFormat::default(),
+            weight: 1.0,
+        }
+    }
+
+    /// Set the scheme assumed for entries without an explicit scheme.
+    pub fn default_scheme(mut self, scheme: ProxyScheme) -> Self {
+        self.default_scheme = scheme;
+        self
+    }
+
+    /// Set the format of the proxy list.
+    pub fn format(mut self, format: SourceFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Set the selection weight of proxies from this source.
+    pub fn weight(mut self, weight: f64) -> Self {
+        self.weight = weight;
+        self
+    }
+}
+
+#[async_trait]
+impl ProxySource for EnvSource {
+    fn name(&self) -> String {
+        format!("env:{}", self.var)
+    }
+
+    async fn fetch(&self) -> Result<Vec<ProxyEntry>, SourceError> {
+        Ok(self.fetch_list().await?.entries)
+    }
+
+    async fn fetch_list(&self) -> Result<FetchedList, SourceError> {
+        let content = std::env::var(&self.var).map_err(|_| SourceError::MissingEnvVar(self.var.clone()))?;
+        utils::parse_source_content(&content, &self.name(), self.default_scheme, &self.format)
+    }
+
+    fn weight(&self) -> f64 {
+        self.weight
+    }
+}
+
+/// Proxies from the standard `HTTP_PROXY`, `HTTPS_PROXY` and `ALL_PROXY` variables.
+///
+/// Lowercase variants are read as well. Entries without a scheme default to HTTP,
+/// following the usual convention for these variables.
+#[derive(Debug, Clone)]
+pub struct SystemProxySource {
+    weight: f64,
+}
+
+impl SystemProxySource {
+    /// Variables read by this source.
+    const VARS: [&'static str; 6] = ["HTTP_PROXY", "http_proxy", "HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy"];
+
+    /// Create a source reading the standard proxy variables.
+    pub fn new() -> Self {
+        Self { weight: 1.0 }
+    }
+
+    /// Set the selection weight of proxies from this source.
+    pub fn weight(mut self, weight: f64) -> Self {
+        self.weight = weight;
+        self
+    }
+}
+
+impl Default for SystemProxySource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl ProxySource for SystemProxySource {
+    fn name(&self) -> String {
+        "system-env".to_string()
+    }
+
+    async fn fetch(&self) -> Result<Vec<ProxyEntry>, SourceError> {
+        let content: Vec<String> = Self::VARS.iter().filter_map(|var| std::env::var(var).ok()).collect();
+        Ok(utils::parse_source_content(&content.join("\n"), &self.name(), ProxyScheme::Http, &SourceFormat::Text)?.entries)
+    }
+
+    fn weight(&self) -> f64 {
+        self.weight
+    }
+}
+
+/// A proxy list piped into the process on standard input.
+///
+/// Standard input is read to the end on the first fetch, later fetches
+/// reuse that content.
+#[derive(Debug)]
+pub struct StdinSource {
+    content: OnceCell<String>,
+    default_scheme: ProxyScheme,
+    format: SourceFormat,
+    weight: f64,
+}
+
+impl StdinSource {
+    /// Create a plain-text source whose bare entries default to SOCKS5.
+    pub fn new() -> Self {
+        Self {
+            content: OnceCell::new(),
+            default_scheme: ProxyScheme::default(),
+            format: SourceFormat::default(),
+            weight: 1.0,
+        }
+    }
+
+    /// Set the scheme assumed for entries without an explicit scheme.
+    pub fn default_scheme(mut self, scheme: ProxyScheme) -> Self {
+        self.default_scheme = scheme;
+        self
+    }
+
+    /// Set the format of the proxy list.
+    pub fn format(mut self, format: SourceFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Set the selection weight of proxies from this source.
+    pub fn weight(mut self, weight: f64) -> Self {
+        self.weight = weight;
+        self
+    }
+}
+
+impl Default for StdinSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl ProxySource for StdinSource {
+    fn name(&self) -> String {
+        "stdin".to_string()
+    }
+
+    async fn fetch(&self) -> Result<Vec<ProxyEntry>, SourceError> {
+        Ok(self.fetch_list().await?.entries)
+    }
+
+    async fn fetch_list(&self) -> Result<FetchedList, SourceError> {
+        let content = self.content
+            .get_or_try_init(|| async {
+                let mut content = String::new();
+                tokio::io::stdin().read_to_string(&mut content).await.map_err(|error| SourceError::Io {
+                    path: PathBuf::from("<stdin>"),
+                    error,
+                })?;
+                Ok::<_, SourceError>(content)
+            })
+            .await?;
+        utils::parse_source_content(content, &self.name(), self.default_scheme, &self.format)
+    }
+
+    fn weight(&self) -> f64 {
+        self.weight
+    }
+}
+
+/// A fixed list of proxies provided in code.
+#[derive(Debug, Clone)]
+pub struct StaticSource {
+    name: String,
+    entries: Vec<ProxyEntry>,
+    weight: f64,
+}
+
+impl StaticSource {
+    /// Create a source that always yields the given entries.
+    pub fn new(entries: Vec<ProxyEntry>) -> Self {
+        Self {
+            name: "static".to_string(),
+            entries,
+            weight: 1.0,
+        }
+    }
+
+    /// Set the name of the source used in logs.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Set the selection weight of proxies from this source.
+    pub fn weight(mut self, weight: f64) -> Self {
+        self.weight = weight;
+        self
+    }
+}
+
+#[async_trait]
+impl ProxySource for StaticSource {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    async fn fetch(&self) -> Result<Vec<ProxyEntry>, SourceError> {
+        Ok(self.entries.clone())
+    }
+
+    fn weight(&self) -> f64 {
+        self.weight
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn http_locations() {
+        let cases = [
+            ("http://example.com/list.txt", true),
+            ("https://example.com/list.txt", true),
+            ("HTTPS://example.com/list.txt", true),
+            ("http_proxies.txt", false),
+            ("httpd/list.txt", false),
+            ("https", false),
+            ("/srv/http://list.txt", false),
+        ];
+        for (location, expected) in cases {
+            assert_eq!(is_http_url(location), expected, "{}", location);
+        }
+    }
+}